@@ -34,7 +34,6 @@ fn execute_argon2() {
     let init_res: Response = instantiate(&mut deps, mock_env(), init_info, Empty {}).unwrap();
     assert_eq!(0, init_res.messages.len());
 
-    let gas_before = deps.get_gas_left();
     let _execute_res: Response = execute(
         &mut deps,
         mock_env(),
@@ -45,7 +44,7 @@ fn execute_argon2() {
         },
     )
     .unwrap();
-    let gas_used = gas_before - deps.get_gas_left();
+    let gas_used = deps.create_gas_report().limit - deps.get_gas_left();
     // Note: the exact gas usage depends on the Rust version used to compile Wasm,
     // which we only fix when using rust-optimizer, not integration tests.
     let expected = 8635688250000; // +/- 20%
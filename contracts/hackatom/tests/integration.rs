@@ -389,7 +389,6 @@ fn execute_allocate_large_memory() {
 
     // Grow by 48 pages (3 MiB)
     let execute_info = mock_info(creator.as_str(), &[]);
-    let gas_before = deps.get_gas_left();
     let execute_res: Response = execute(
         &mut deps,
         mock_env(),
@@ -401,7 +400,7 @@ fn execute_allocate_large_memory() {
         execute_res.data.unwrap(),
         Binary::from((pages_before as u32).to_be_bytes())
     );
-    let gas_used = gas_before - deps.get_gas_left();
+    let gas_used = deps.create_gas_report().limit - deps.get_gas_left();
     // Gas consumption is relatively small
     // Note: the exact gas usage depends on the Rust version used to compile Wasm,
     // which we only fix when using rust-optimizer, not integration tests.
@@ -412,7 +411,6 @@ fn execute_allocate_large_memory() {
 
     // Grow by 1600 pages (100 MiB)
     let execute_info = mock_info(creator.as_str(), &[]);
-    let gas_before = deps.get_gas_left();
     let result: ContractResult<Response> = execute(
         &mut deps,
         mock_env(),
@@ -420,7 +418,7 @@ fn execute_allocate_large_memory() {
         ExecuteMsg::AllocateLargeMemory { pages: 1600 },
     );
     assert_eq!(result.unwrap_err(), "Generic error: memory.grow failed");
-    let gas_used = gas_before - deps.get_gas_left();
+    let gas_used = deps.create_gas_report().limit - deps.get_gas_left();
     // Gas consumption is relatively small
     // Note: the exact gas usage depends on the Rust version used to compile Wasm,
     // which we only fix when using rust-optimizer, not integration tests.
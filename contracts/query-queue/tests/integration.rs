@@ -43,7 +43,7 @@ fn create_contract() -> (Instance<MockApi, MockStorage, MockQuerier>, MessageInf
     let gas_limit = 1_000_000_000_000; // ~1ms, enough for many executions within one instance
     let instance_options = InstanceOptions {
         gas_limit,
-        print_debug: false,
+        ..Default::default()
     };
     let mut deps = Backend {
         api: MockApi::default(),
@@ -151,7 +151,7 @@ fn create_integrated_query_contract() -> (Instance<MockApi, MockStorage, MockQue
     let gas_limit = 1_000_000_000_000; // ~1ms, enough for many executions within one instance
     let instance_options = InstanceOptions {
         gas_limit,
-        print_debug: false,
+        ..Default::default()
     };
     let mut deps = Backend {
         api: MockApi::default(),
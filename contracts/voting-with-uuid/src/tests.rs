@@ -3,7 +3,7 @@ use crate::error::ContractError;
 use crate::msg::{ExecuteMsg, InstantiateMsg, PollResponse, QueryMsg};
 use crate::state::{config_read, PollStatus, State};
 use cosmwasm_std::testing::{
-    mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
+    assert_no_messages, mock_dependencies, mock_dependencies_with_balance, mock_env, mock_info,
 };
 use cosmwasm_std::{
     attr, coins, from_binary, Addr, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, Response,
@@ -47,7 +47,7 @@ fn proper_initialization() {
     let msg = init_msg();
     let info = mock_info(TEST_CREATOR, &coins(2, VOTING_TOKEN));
     let res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, res.messages.len());
+    assert_no_messages(&res);
 
     let state = config_read(&deps.storage).load().unwrap();
     assert_eq!(
@@ -802,7 +802,7 @@ fn fails_insufficient_funds() {
     let msg = init_msg();
     let info = mock_info(TEST_VOTER, &coins(2, VOTING_TOKEN));
     let init_res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, init_res.messages.len());
+    assert_no_messages(&init_res);
 
     // insufficient token
     let msg = ExecuteMsg::StakeVotingTokens {};
@@ -825,7 +825,7 @@ fn fails_staking_wrong_token() {
     let msg = init_msg();
     let info = mock_info(TEST_VOTER, &coins(2, VOTING_TOKEN));
     let init_res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
-    assert_eq!(0, init_res.messages.len());
+    assert_no_messages(&init_res);
 
     // wrong token
     let msg = ExecuteMsg::StakeVotingTokens {};
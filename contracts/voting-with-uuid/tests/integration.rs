@@ -40,14 +40,12 @@ fn compare_gas_spent() {
     assert_eq!(res.messages.len(), 0);
 
     let uuid_msg = ExecuteMsg::MakeUuid {};
-    let before_gas1 = deps.get_gas_left();
     let _execute_res: Response = execute(&mut deps, env.clone(), info.clone(), uuid_msg).unwrap();
-    let gas_used_uuid = before_gas1 - deps.get_gas_left();
+    let gas_used_uuid = deps.create_gas_report().limit - deps.get_gas_left();
 
     let seq_msg = ExecuteMsg::MakeSequenceId {};
-    let before_gas2 = deps.get_gas_left();
     let _execute_res: Response = execute(&mut deps, env, info, seq_msg).unwrap();
-    let gas_used_seq_id = before_gas2 - deps.get_gas_left();
+    let gas_used_seq_id = deps.create_gas_report().limit - deps.get_gas_left();
 
     assert!(gas_used_seq_id < gas_used_uuid);
     println!("gas_seq_id {} gas_uuid {}", gas_used_seq_id, gas_used_uuid);
@@ -0,0 +1,51 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{DataStruct, DeriveInput, Error, Ident, Result};
+
+use crate::into_event::attribute_fields;
+
+/// generate an ast for `fn into_attributes(self) -> Vec<cosmwasm_std::Attribute>` from a struct
+fn make_into_attributes_from_struct(
+    id: Ident,
+    struct_data: DataStruct,
+) -> Result<proc_macro2::TokenStream> {
+    let attributes = attribute_fields("IntoAttributes", struct_data)?
+        .into_iter()
+        .map(
+            |(field_id, value)| quote!(cosmwasm_std::Attribute::new(stringify!(#field_id), #value)),
+        );
+
+    let gen = quote!(
+        impl #id {
+            pub fn into_attributes(self) -> Vec<cosmwasm_std::Attribute> {
+                vec![#(#attributes),*]
+            }
+        }
+    );
+    Ok(gen)
+}
+
+/// derive `IntoAttributes` from a derive input. The input needs to be a struct.
+pub fn derive_into_attributes(input: DeriveInput) -> TokenStream {
+    match input.data {
+        syn::Data::Struct(struct_data) => {
+            make_into_attributes_from_struct(input.ident, struct_data)
+                .unwrap_or_else(|e| e.to_compile_error())
+                .into()
+        }
+        syn::Data::Enum(enum_data) => Error::new(
+            enum_data.enum_token.span,
+            "[IntoAttributes] `derive(IntoAttributes)` cannot be applied to Enum.",
+        )
+        .to_compile_error()
+        .into(),
+        syn::Data::Union(union_data) => Error::new(
+            union_data.union_token.span,
+            "[IntoAttributes] `derive(IntoAttributes)` cannot be applied to Union.",
+        )
+        .to_compile_error()
+        .into(),
+    }
+}
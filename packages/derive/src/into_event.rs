@@ -7,7 +7,7 @@ use syn::spanned::Spanned;
 use syn::{Attribute, DataStruct, DeriveInput, Error, Field, Ident, Result};
 
 /// scan attrs and get `to_string_fn` attribute
-fn scan_to_string_fn(field: &Field) -> Result<Option<proc_macro2::TokenStream>> {
+fn scan_to_string_fn(derive_name: &str, field: &Field) -> Result<Option<proc_macro2::TokenStream>> {
     let filtered: Vec<&Attribute> = field
         .attrs
         .iter()
@@ -16,7 +16,10 @@ fn scan_to_string_fn(field: &Field) -> Result<Option<proc_macro2::TokenStream>>
     if filtered.len() > 1 {
         return Err(Error::new(
             field.span(),
-            "[IntoEvent] Only one or zero `to_string_fn` can be applied to one field.",
+            format!(
+                "[{}] Only one or zero `to_string_fn` can be applied to one field.",
+                derive_name
+            ),
         ));
     };
     if filtered.is_empty() {
@@ -27,7 +30,7 @@ fn scan_to_string_fn(field: &Field) -> Result<Option<proc_macro2::TokenStream>>
 }
 
 /// scan attrs and return if it has any `to_string`
-fn has_use_to_string(field: &Field) -> Result<bool> {
+fn has_use_to_string(derive_name: &str, field: &Field) -> Result<bool> {
     let mut filtered = field
         .attrs
         .iter()
@@ -35,43 +38,56 @@ fn has_use_to_string(field: &Field) -> Result<bool> {
     if filtered.clone().any(|a| !a.tokens.is_empty()) {
         return Err(Error::new(
             field.span(),
-            "[IntoEvent] attribute `use_to_string` has some value. If you intend to specify the cast function to string, use `to_string_fn` instead.",
+            format!("[{}] attribute `use_to_string` has some value. If you intend to specify the cast function to string, use `to_string_fn` instead.", derive_name),
         ));
     }
     Ok(filtered.next().is_some())
 }
 
-/// generate an ast for `impl Into<cosmwasm::Event>` from a struct
-fn make_init_from_struct(id: Ident, struct_data: DataStruct) -> Result<proc_macro2::TokenStream> {
-    // snake case of struct ident
-    let name = id.to_string().as_str().to_case(Case::Snake);
-
-    // generate the body of `fn into`
-    // generating `Event::new()` part
-    let mut fn_body = quote!(
-        cosmwasm_std::Event::new(#name)
-    );
-
-    // chain `.add_attribute`s to `Event::new()` part
+/// generate the attribute key and an expression for the attribute value of every field.
+/// The value expressions take into account `to_string_fn` and `use_to_string`.
+pub(crate) fn attribute_fields(
+    derive_name: &str,
+    struct_data: DataStruct,
+) -> Result<Vec<(Ident, proc_macro2::TokenStream)>> {
+    let mut out = Vec::new();
     for field in struct_data.fields {
         let field_id = match field.clone().ident {
             None => {
                 return Err(Error::new(
                     field.span(),
-                    "[IntoEvent] Unexpected unnamed field.",
+                    format!("[{}] Unexpected unnamed field.", derive_name),
                 ))
             }
             Some(field_id) => field_id,
         };
-        let value = match (scan_to_string_fn(&field)?, has_use_to_string(&field)?) {
+        let value = match (scan_to_string_fn(derive_name, &field)?, has_use_to_string(derive_name, &field)?) {
             (Some(_), true) => return Err(Error::new(
                 field.span(),
-                "[IntoEvent] Both `use_to_string` and `to_string_fn` are applied to an field. Only one can be applied.",
+                format!("[{}] Both `use_to_string` and `to_string_fn` are applied to an field. Only one can be applied.", derive_name),
             )),
             (Some(to_string_fn), false) => quote!(#to_string_fn(self.#field_id)),
             (None, true) => quote!(self.#field_id.to_string()),
             (None, false) => quote!(self.#field_id),
         };
+        out.push((field_id, value));
+    }
+    Ok(out)
+}
+
+/// generate an ast for `impl Into<cosmwasm::Event>` from a struct
+fn make_init_from_struct(id: Ident, struct_data: DataStruct) -> Result<proc_macro2::TokenStream> {
+    // snake case of struct ident
+    let name = id.to_string().as_str().to_case(Case::Snake);
+
+    // generate the body of `fn into`
+    // generating `Event::new()` part
+    let mut fn_body = quote!(
+        cosmwasm_std::Event::new(#name)
+    );
+
+    // chain `.add_attribute`s to `Event::new()` part
+    for (field_id, value) in attribute_fields("IntoEvent", struct_data)? {
         fn_body.extend(quote!(
             .add_attribute(stringify!(#field_id), #value)
         ))
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate syn;
 
+mod into_attributes;
 mod into_event;
 
 use proc_macro::TokenStream;
@@ -53,6 +54,9 @@ use std::str::FromStr;
 ///
 /// where `InstantiateMsg`, `ExecuteMsg`, and `QueryMsg` are contract defined
 /// types that implement `DeserializeOwned + JsonSchema`.
+///
+/// `migrate` can optionally take a `MessageInfo` after `env` to learn who triggered
+/// the migration. The host passes it only to contracts that declare it.
 #[proc_macro_attribute]
 pub fn entry_point(_attr: TokenStream, mut item: TokenStream) -> TokenStream {
     let cloned = item.clone();
@@ -65,6 +69,12 @@ pub fn entry_point(_attr: TokenStream, mut item: TokenStream) -> TokenStream {
     let typed_ptrs = (0..args).fold(String::new(), |acc, i| format!("{}ptr{}: u32, ", acc, i));
     // E.g. "ptr0, ptr1, ptr2, "
     let ptrs = (0..args).fold(String::new(), |acc, i| format!("{}ptr{}, ", acc, i));
+    // A migrate function that takes a `MessageInfo` needs a different wrapper
+    let do_name = if name == "migrate" && args == 3 {
+        "migrate_with_info".to_string()
+    } else {
+        name.clone()
+    };
 
     let new_code = format!(
         r##"
@@ -72,11 +82,12 @@ pub fn entry_point(_attr: TokenStream, mut item: TokenStream) -> TokenStream {
         mod __wasm_export_{name} {{ // new module to avoid conflict of function name
             #[no_mangle]
             extern "C" fn {name}({typed_ptrs}) -> u32 {{
-                cosmwasm_std::do_{name}(&super::{name}, {ptrs})
+                cosmwasm_std::do_{do_name}(&super::{name}, {ptrs})
             }}
         }}
     "##,
         name = name,
+        do_name = do_name,
         typed_ptrs = typed_ptrs,
         ptrs = ptrs
     );
@@ -126,3 +137,40 @@ pub fn derive_into_event(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as syn::DeriveInput);
     into_event::derive_into_event(derive_input)
 }
+
+/// generate an ast for `fn into_attributes(self) -> Vec<cosmwasm::Attribute>` from a struct
+///
+/// The fields are converted the same way as for [`IntoEvent`](derive@IntoEvent),
+/// i.e. `use_to_string` and `to_string_fn` can be applied to fields.
+///
+/// Structure:
+///
+/// ```no_test
+/// #[derive(IntoAttributes)]
+/// struct StructName {
+///     field_name_1: field_type_1,
+///     #[use_to_string]
+///     field_name_2: field_type_2,
+///     #[to_string_fn(cast_fn_3)]
+///     field_name_3: field_type_3,
+/// }
+/// ```
+///
+/// Output AST:
+///
+/// ```no_test
+/// impl `StructName` {
+///     pub fn into_attributes(self) -> Vec<Attribute> {
+///         vec![
+///             Attribute::new("field_name_1", self.field_value_1),
+///             Attribute::new("field_name_2", self.field_value_2.to_string()),
+///             Attribute::new("field_name_3", casting_fn(self.field_value_3)),
+///         ]
+///     }
+/// }
+/// ```
+#[proc_macro_derive(IntoAttributes, attributes(to_string_fn, use_to_string))]
+pub fn derive_into_attributes(input: TokenStream) -> TokenStream {
+    let derive_input = parse_macro_input!(input as syn::DeriveInput);
+    into_attributes::derive_into_attributes(derive_input)
+}
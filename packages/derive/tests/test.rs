@@ -1,7 +1,7 @@
 extern crate cosmwasm_derive;
 
-use cosmwasm_derive::IntoEvent;
-use cosmwasm_std::{attr, coins, Addr, Coin, Event};
+use cosmwasm_derive::{IntoAttributes, IntoEvent};
+use cosmwasm_std::{attr, coins, Addr, Coin, Empty, Event, Response};
 
 fn coins_to_string(coins: Vec<Coin>) -> String {
     format!(
@@ -80,3 +80,65 @@ fn no_fields() {
     let a_event: Event = a.into();
     assert_eq!(a_event, expected);
 }
+
+#[test]
+fn into_attributes_basic() {
+    #[derive(IntoAttributes)]
+    struct Config {
+        #[use_to_string]
+        id: u64,
+        owner: Addr,
+        name: String,
+        label: &'static str,
+        #[use_to_string]
+        enabled: bool,
+        #[to_string_fn(coins_to_string)]
+        fee: Vec<Coin>,
+    }
+
+    let config = Config {
+        id: 42,
+        owner: Addr::unchecked("alice"),
+        name: "config".to_string(),
+        label: "test",
+        enabled: true,
+        fee: coins(42, "link"),
+    };
+    let expected = vec![
+        attr("id", "42"),
+        attr("owner", "alice"),
+        attr("name", "config"),
+        attr("label", "test"),
+        attr("enabled", "true"),
+        attr("fee", coins_to_string(coins(42, "link"))),
+    ];
+    assert_eq!(config.into_attributes(), expected);
+}
+
+#[test]
+fn into_attributes_can_be_added_to_response() {
+    #[derive(IntoAttributes)]
+    struct Config {
+        owner: Addr,
+        #[use_to_string]
+        max_count: u32,
+    }
+
+    let config = Config {
+        owner: Addr::unchecked("alice"),
+        max_count: 7,
+    };
+    let response = Response::<Empty>::new().add_attributes(config.into_attributes());
+    assert_eq!(
+        response.attributes,
+        vec![attr("owner", "alice"), attr("max_count", "7")]
+    );
+}
+
+#[test]
+fn into_attributes_no_fields() {
+    #[derive(IntoAttributes)]
+    struct A {}
+
+    assert!(A {}.into_attributes().is_empty());
+}
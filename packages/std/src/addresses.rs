@@ -151,7 +151,7 @@ impl<'a> From<&'a Addr> for Cow<'a, Addr> {
 /// So the type shoud be treated as a marker to express the intended data type, not as
 /// a validity guarantee of any sort.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash, JsonSchema)]
-pub struct CanonicalAddr(pub Binary);
+pub struct CanonicalAddr(#[schemars(with = "String")] pub Binary);
 
 /// Implement `CanonicalAddr == Binary`
 impl PartialEq<Binary> for CanonicalAddr {
@@ -260,6 +260,15 @@ impl CanonicalAddr {
     pub fn as_slice(&self) -> &[u8] {
         self.0.as_slice()
     }
+
+    /// Compares two canonical addresses in constant time with respect to their content.
+    ///
+    /// Unlike `==`, this does not return early at the first differing byte, such that
+    /// the time taken does not leak the length of the common prefix. Only the lengths
+    /// of the two addresses are compared in variable time.
+    pub fn ct_eq(&self, other: &CanonicalAddr) -> bool {
+        crate::ct_compare(self.as_slice(), other.as_slice())
+    }
 }
 
 impl fmt::Display for CanonicalAddr {
@@ -278,6 +287,33 @@ mod tests {
     use std::collections::HashSet;
     use std::hash::{Hash, Hasher};
 
+    #[test]
+    fn addr_json_schema_is_string() {
+        let schema = schemars::schema_for!(Addr);
+        assert_eq!(schema.schema.metadata.unwrap().title.unwrap(), "Addr");
+        assert_eq!(
+            schema.schema.instance_type,
+            Some(schemars::schema::InstanceType::String.into())
+        );
+        assert!(schema.definitions.is_empty());
+    }
+
+    #[test]
+    fn canonical_addr_json_schema_is_string() {
+        let schema = schemars::schema_for!(CanonicalAddr);
+        assert_eq!(
+            schema.schema.metadata.unwrap().title.unwrap(),
+            "CanonicalAddr"
+        );
+        assert_eq!(
+            schema.schema.instance_type,
+            Some(schemars::schema::InstanceType::String.into())
+        );
+        // no reference to Binary
+        assert!(schema.schema.subschemas.is_none());
+        assert!(schema.definitions.is_empty());
+    }
+
     #[test]
     fn addr_unchecked_works() {
         let a = Addr::unchecked("123");
@@ -473,6 +509,30 @@ mod tests {
         assert_eq!(canonical_addr.len(), bytes.len());
     }
 
+    #[test]
+    fn canonical_addr_ct_eq_works() {
+        let a = CanonicalAddr::from(vec![0u8, 187, 61, 11, 250, 0]);
+        let b = CanonicalAddr::from(vec![0u8, 187, 61, 11, 250, 0]);
+        let c = CanonicalAddr::from(vec![0u8, 187, 61, 11, 250, 1]);
+        let d = CanonicalAddr::from(vec![0u8, 187, 61, 11, 250]);
+        let empty = CanonicalAddr::from(vec![]);
+
+        for (x, y) in [
+            (&a, &b),
+            (&a, &c),
+            (&a, &d),
+            (&c, &d),
+            (&empty, &empty),
+            (&a, &empty),
+        ] {
+            assert_eq!(x.ct_eq(y), x == y);
+            assert_eq!(y.ct_eq(x), y == x);
+        }
+        assert!(a.ct_eq(&b));
+        assert!(!a.ct_eq(&c));
+        assert!(!a.ct_eq(&d));
+    }
+
     #[test]
     fn canonical_addr_is_empty() {
         let bytes: &[u8] = &[0u8, 187, 61, 11, 250, 0];
@@ -0,0 +1,109 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{from_slice, to_vec};
+use crate::{StdError, StdResult, Storage};
+
+/// The storage key under which the [`ContractVersion`] is stored. Contracts must not
+/// use this key for anything else.
+const CONTRACT_VERSION_KEY: &[u8] = b"contract_info";
+
+/// Name and version of the code that created or last migrated a contract's state.
+///
+/// Store it in `instantiate` and `migrate` using [`set_contract_version`] and check it in
+/// `migrate` using [`ensure_contract_name`] to avoid running migrations on the state of
+/// an unrelated contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ContractVersion {
+    /// The crate name of the implementing contract, e.g. `crates.io:cw20-base`
+    pub contract: String,
+    /// The version of the implementing contract, e.g. `0.14.2`
+    pub version: String,
+}
+
+/// Stores the contract name and version, replacing the existing one
+pub fn set_contract_version(
+    storage: &mut dyn Storage,
+    contract: impl Into<String>,
+    version: impl Into<String>,
+) -> StdResult<()> {
+    let value = ContractVersion {
+        contract: contract.into(),
+        version: version.into(),
+    };
+    storage.set(CONTRACT_VERSION_KEY, &to_vec(&value)?);
+    Ok(())
+}
+
+/// Loads the contract name and version. Returns a not found error if none was stored.
+pub fn get_contract_version(storage: &dyn Storage) -> StdResult<ContractVersion> {
+    match storage.get(CONTRACT_VERSION_KEY) {
+        Some(data) => from_slice(&data),
+        None => Err(StdError::not_found("ContractVersion")),
+    }
+}
+
+/// Checks that the stored contract name equals `expected`. Use this in `migrate` before
+/// touching the state to prevent migrating the state of a different contract.
+pub fn ensure_contract_name(storage: &dyn Storage, expected: &str) -> StdResult<()> {
+    let stored = get_contract_version(storage)?;
+    if stored.contract != expected {
+        return Err(StdError::generic_err(format!(
+            "Contract name mismatch. Stored: {}, expected: {}",
+            stored.contract, expected
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::MockStorage;
+
+    #[test]
+    fn set_and_get_contract_version_works() {
+        let mut storage = MockStorage::new();
+        match get_contract_version(&storage).unwrap_err() {
+            StdError::NotFound { kind, .. } => assert_eq!(kind, "ContractVersion"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        set_contract_version(&mut storage, "crates.io:hackatom", "1.0.0").unwrap();
+        assert_eq!(
+            get_contract_version(&storage).unwrap(),
+            ContractVersion {
+                contract: "crates.io:hackatom".to_string(),
+                version: "1.0.0".to_string(),
+            }
+        );
+
+        set_contract_version(&mut storage, "crates.io:hackatom", "1.1.0").unwrap();
+        assert_eq!(get_contract_version(&storage).unwrap().version, "1.1.0");
+    }
+
+    #[test]
+    fn ensure_contract_name_works() {
+        let mut storage = MockStorage::new();
+        set_contract_version(&mut storage, "crates.io:hackatom", "1.0.0").unwrap();
+
+        // matching name proceeds
+        ensure_contract_name(&storage, "crates.io:hackatom").unwrap();
+
+        // mismatched name errors
+        match ensure_contract_name(&storage, "crates.io:cw20-base").unwrap_err() {
+            StdError::GenericErr { msg, .. } => assert_eq!(
+                msg,
+                "Contract name mismatch. Stored: crates.io:hackatom, expected: crates.io:cw20-base"
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // missing version errors
+        let storage = MockStorage::new();
+        match ensure_contract_name(&storage, "crates.io:hackatom").unwrap_err() {
+            StdError::NotFound { kind, .. } => assert_eq!(kind, "ContractVersion"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+}
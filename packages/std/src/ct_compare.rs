@@ -0,0 +1,63 @@
+use subtle::ConstantTimeEq;
+
+/// Compares two byte slices in constant time, i.e. the time taken does not depend on
+/// the position of the first differing byte.
+///
+/// Use this instead of `==` when comparing secrets like passwords or tokens against user
+/// input to avoid leaking information about the secret through timing. Note that the
+/// lengths of the inputs are not considered secret: slices of different length are
+/// unequal right away.
+///
+/// The comparison is done by [`subtle::ConstantTimeEq`], which hides the intermediate
+/// results from the optimizer such that the loop over the bytes cannot be short-circuited.
+pub fn ct_compare(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ct_compare_works() {
+        assert!(ct_compare(b"", b""));
+        assert!(ct_compare(b"a", b"a"));
+        assert!(ct_compare(b"password", b"password"));
+        assert!(ct_compare(&[0xff; 100], &[0xff; 100]));
+
+        assert!(!ct_compare(b"a", b"b"));
+        assert!(!ct_compare(b"password", b"Password"));
+        assert!(!ct_compare(b"password", b"passworD"));
+        assert!(!ct_compare(b"password", b"passw0rd"));
+
+        // different lengths
+        assert!(!ct_compare(b"", b"a"));
+        assert!(!ct_compare(b"password", b"password1"));
+        assert!(!ct_compare(b"password1", b"password"));
+    }
+
+    #[test]
+    fn ct_compare_matches_eq() {
+        let inputs: &[&[u8]] = &[
+            b"",
+            b"\x00",
+            b"\x01",
+            b"abc",
+            b"abd",
+            b"bbc",
+            b"abcd",
+            &[0x00; 32],
+            &[0xff; 32],
+            &[0x00; 33],
+        ];
+        for a in inputs {
+            for b in inputs {
+                assert_eq!(ct_compare(a, b), a == b, "a: {:?}, b: {:?}", a, b);
+            }
+        }
+    }
+}
@@ -8,7 +8,7 @@ pub use hash_calculation_error::HashCalculationError;
 pub use recover_pubkey_error::RecoverPubkeyError;
 pub use std_error::{
     CheckedFromRatioError, CheckedMultiplyRatioError, ConversionOverflowError, DivideByZeroError,
-    OverflowError, OverflowOperation, RoundUpOverflowError, StdError, StdResult,
+    OverflowError, OverflowOperation, RoundUpOverflowError, StdError, StdResult, UuidError,
 };
 pub use system_error::SystemError;
 pub use verification_error::VerificationError;
@@ -20,6 +20,7 @@ use crate::errors::{HashCalculationError, RecoverPubkeyError, VerificationError}
 /// Checklist for adding a new error:
 /// - Add enum case
 /// - Add creator function in std_error_helpers.rs
+/// - Assign a new code in [`StdError::code`]
 #[derive(Error, Debug)]
 pub enum StdError {
     #[error("Verification error: {source}")]
@@ -224,6 +225,46 @@ impl StdError {
             backtrace: Backtrace::capture(),
         }
     }
+
+    /// A stable numeric code of the error case, which hosts can pass on to clients
+    /// along with the message.
+    ///
+    /// | Case                   | Code |
+    /// | ---------------------- | ---- |
+    /// | `VerificationErr`      | 1    |
+    /// | `RecoverPubkeyErr`     | 2    |
+    /// | `HashCalculationError` | 3    |
+    /// | `GenericErr`           | 4    |
+    /// | `InvalidBase64`        | 5    |
+    /// | `InvalidDataSize`      | 6    |
+    /// | `InvalidHex`           | 7    |
+    /// | `InvalidUtf8`          | 8    |
+    /// | `NotFound`             | 9    |
+    /// | `ParseErr`             | 10   |
+    /// | `SerializeErr`         | 11   |
+    /// | `Overflow`             | 12   |
+    /// | `DivideByZero`         | 13   |
+    /// | `ConversionOverflow`   | 14   |
+    ///
+    /// Codes are never changed or reused. New cases get the next free code.
+    pub fn code(&self) -> u32 {
+        match self {
+            StdError::VerificationErr { .. } => 1,
+            StdError::RecoverPubkeyErr { .. } => 2,
+            StdError::HashCalculationError { .. } => 3,
+            StdError::GenericErr { .. } => 4,
+            StdError::InvalidBase64 { .. } => 5,
+            StdError::InvalidDataSize { .. } => 6,
+            StdError::InvalidHex { .. } => 7,
+            StdError::InvalidUtf8 { .. } => 8,
+            StdError::NotFound { .. } => 9,
+            StdError::ParseErr { .. } => 10,
+            StdError::SerializeErr { .. } => 11,
+            StdError::Overflow { .. } => 12,
+            StdError::DivideByZero { .. } => 13,
+            StdError::ConversionOverflow { .. } => 14,
+        }
+    }
 }
 
 impl PartialEq<StdError> for StdError {
@@ -493,6 +534,12 @@ impl From<HashCalculationError> for StdError {
     }
 }
 
+impl From<UuidError> for StdError {
+    fn from(source: UuidError) -> Self {
+        Self::parse_err("Uuid", source.msg)
+    }
+}
+
 impl From<OverflowError> for StdError {
     fn from(source: OverflowError) -> Self {
         Self::overflow(source)
@@ -592,6 +639,21 @@ impl DivideByZeroError {
     }
 }
 
+/// The error returned when parsing a [`Uuid`](crate::Uuid) from a string fails.
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("Invalid UUID: {msg}")]
+pub struct UuidError {
+    pub msg: String,
+}
+
+impl UuidError {
+    pub fn new(msg: impl ToString) -> Self {
+        Self {
+            msg: msg.to_string(),
+        }
+    }
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum CheckedMultiplyRatioError {
     #[error("Denominator must not be zero")]
@@ -819,6 +881,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn from_uuid_error_works() {
+        let error: StdError = UuidError::new("invalid character").into();
+        match error {
+            StdError::ParseErr {
+                target_type, msg, ..
+            } => {
+                assert_eq!(target_type, "Uuid");
+                assert_eq!(msg, "invalid character");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn implements_debug() {
         let error: StdError = StdError::from(OverflowError::new(OverflowOperation::Sub, 3, 5));
@@ -877,4 +953,42 @@ mod tests {
             err => panic!("Unexpected error: {:?}", err),
         }
     }
+
+    #[test]
+    fn code_works() {
+        let errors = [
+            (StdError::verification_err(VerificationError::GenericErr), 1),
+            (
+                StdError::recover_pubkey_err(RecoverPubkeyError::unknown_err(1)),
+                2,
+            ),
+            (
+                StdError::hash_calculation_err(HashCalculationError::InputsTooLarger),
+                3,
+            ),
+            (StdError::generic_err("foo"), 4),
+            (StdError::invalid_base64("foo"), 5),
+            (StdError::invalid_data_size(1, 2), 6),
+            (StdError::invalid_hex("foo"), 7),
+            (StdError::invalid_utf8("foo"), 8),
+            (StdError::not_found("foo"), 9),
+            (StdError::parse_err("Foo", "bar"), 10),
+            (StdError::serialize_err("Foo", "bar"), 11),
+            (
+                StdError::overflow(OverflowError::new(OverflowOperation::Add, 1, 2)),
+                12,
+            ),
+            (StdError::divide_by_zero(DivideByZeroError::new(1)), 13),
+            (
+                StdError::from(ConversionOverflowError::new("Uint256", "Uint128", "1")),
+                14,
+            ),
+        ];
+        for (error, code) in &errors {
+            assert_eq!(error.code(), *code, "{:?}", error);
+        }
+
+        let codes: std::collections::HashSet<u32> = errors.iter().map(|(e, _)| e.code()).collect();
+        assert_eq!(codes.len(), errors.len());
+    }
 }
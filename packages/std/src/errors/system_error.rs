@@ -34,6 +34,30 @@ pub enum SystemError {
     },
 }
 
+impl SystemError {
+    /// A stable numeric code of the error case, which hosts can pass on to clients
+    /// along with the message.
+    ///
+    /// | Case                 | Code |
+    /// | -------------------- | ---- |
+    /// | `InvalidRequest`     | 1    |
+    /// | `InvalidResponse`    | 2    |
+    /// | `NoSuchContract`     | 3    |
+    /// | `Unknown`            | 4    |
+    /// | `UnsupportedRequest` | 5    |
+    ///
+    /// Codes are never changed or reused. New cases get the next free code.
+    pub fn code(&self) -> u32 {
+        match self {
+            SystemError::InvalidRequest { .. } => 1,
+            SystemError::InvalidResponse { .. } => 2,
+            SystemError::NoSuchContract { .. } => 3,
+            SystemError::Unknown {} => 4,
+            SystemError::UnsupportedRequest { .. } => 5,
+        }
+    }
+}
+
 impl std::error::Error for SystemError {}
 
 impl std::fmt::Display for SystemError {
@@ -59,3 +83,48 @@ impl std::fmt::Display for SystemError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn code_works() {
+        let errors = [
+            (
+                SystemError::InvalidRequest {
+                    error: "foo".to_string(),
+                    request: Binary::from(b"bar"),
+                },
+                1,
+            ),
+            (
+                SystemError::InvalidResponse {
+                    error: "foo".to_string(),
+                    response: Binary::from(b"bar"),
+                },
+                2,
+            ),
+            (
+                SystemError::NoSuchContract {
+                    addr: "foo".to_string(),
+                },
+                3,
+            ),
+            (SystemError::Unknown {}, 4),
+            (
+                SystemError::UnsupportedRequest {
+                    kind: "foo".to_string(),
+                },
+                5,
+            ),
+        ];
+        for (error, code) in &errors {
+            assert_eq!(error.code(), *code, "{:?}", error);
+        }
+
+        let codes: HashSet<u32> = errors.iter().map(|(e, _)| e.code()).collect();
+        assert_eq!(codes.len(), errors.len());
+    }
+}
@@ -3,8 +3,8 @@
 //! interface_version_8, allocate and deallocate turn into Wasm exports
 //! as soon as cosmwasm_std is `use`d in the contract, even privately.
 //!
-//! `do_execute`, `do_instantiate`, `do_migrate`, `do_query`, `do_reply`
-//! and `do_sudo` should be wrapped with a extern "C" entry point including
+//! `do_execute`, `do_instantiate`, `do_migrate`, `do_migrate_with_info`, `do_query`,
+//! `do_reply` and `do_sudo` should be wrapped with a extern "C" entry point including
 //! the contract-specific function pointer. This is done via the `#[entry_point]`
 //! macro attribute from cosmwasm-derive.
 use std::marker::PhantomData;
@@ -45,6 +45,10 @@ extern "C" fn requires_stargate() -> () {}
 #[no_mangle]
 extern "C" fn requires_cosmwasm_1_1() -> () {}
 
+#[cfg(feature = "cosmwasm_1_3")]
+#[no_mangle]
+extern "C" fn requires_cosmwasm_1_3() -> () {}
+
 /// interface_version_* exports mark which Wasm VM interface level this contract is compiled for.
 /// They can be checked by cosmwasm_vm.
 /// Update this whenever the Wasm VM interface breaks.
@@ -166,6 +170,38 @@ where
     release_buffer(v) as u32
 }
 
+/// do_migrate_with_info should be wrapped in an external "C" export, containing a contract-specific
+/// function as arg. Unlike [`do_migrate`], the contract receives the `MessageInfo` of the migration,
+/// which allows it to authorize the sender.
+///
+/// - `Q`: custom query type (see QueryRequest)
+/// - `M`: message type for request
+/// - `C`: custom response message type (see CosmosMsg)
+/// - `E`: error type for responses
+pub fn do_migrate_with_info<Q, M, C, E>(
+    migrate_fn: &dyn Fn(DepsMut<Q>, Env, MessageInfo, M) -> Result<Response<C>, E>,
+    env_ptr: u32,
+    info_ptr: u32,
+    msg_ptr: u32,
+) -> u32
+where
+    Q: CustomQuery,
+    M: DeserializeOwned,
+    C: CustomMsg,
+    E: ToString,
+{
+    #[cfg(feature = "abort")]
+    install_panic_handler();
+    let res = _do_migrate_with_info(
+        migrate_fn,
+        env_ptr as *mut Region,
+        info_ptr as *mut Region,
+        msg_ptr as *mut Region,
+    );
+    let v = to_vec(&res).unwrap();
+    release_buffer(v) as u32
+}
+
 /// do_sudo should be wrapped in an external "C" export, containing a contract-specific function as arg
 ///
 /// - `Q`: custom query type (see QueryRequest)
@@ -456,6 +492,30 @@ where
     migrate_fn(deps.as_mut(), env, msg).into()
 }
 
+fn _do_migrate_with_info<Q, M, C, E>(
+    migrate_fn: &dyn Fn(DepsMut<Q>, Env, MessageInfo, M) -> Result<Response<C>, E>,
+    env_ptr: *mut Region,
+    info_ptr: *mut Region,
+    msg_ptr: *mut Region,
+) -> ContractResult<Response<C>>
+where
+    Q: CustomQuery,
+    M: DeserializeOwned,
+    C: CustomMsg,
+    E: ToString,
+{
+    let env: Vec<u8> = unsafe { consume_region(env_ptr) };
+    let info: Vec<u8> = unsafe { consume_region(info_ptr) };
+    let msg: Vec<u8> = unsafe { consume_region(msg_ptr) };
+
+    let env: Env = try_into_contract_result!(from_slice(&env));
+    let info: MessageInfo = try_into_contract_result!(from_slice(&info));
+    let msg: M = try_into_contract_result!(from_slice(&msg));
+
+    let mut deps = make_dependencies();
+    migrate_fn(deps.as_mut(), env, info, msg).into()
+}
+
 fn _do_sudo<Q, M, C, E>(
     sudo_fn: &dyn Fn(DepsMut<Q>, Env, M) -> Result<Response<C>, E>,
     env_ptr: *mut Region,
@@ -32,6 +32,7 @@ extern "C" {
     fn abort(source_ptr: u32);
 
     fn db_read(key: u32) -> u32;
+    fn db_read_range(key: u32, offset: u32, length: u32) -> u32;
     fn db_write(key: u32, value: u32);
     fn db_remove(key: u32);
 
@@ -106,6 +107,24 @@ impl Storage for ExternalStorage {
         Some(data)
     }
 
+    fn get_range(&self, key: &[u8], offset: usize, length: usize) -> Option<Vec<u8>> {
+        let key = build_region(key);
+        let key_ptr = &*key as *const Region as u32;
+        // Values are much shorter than u32::MAX, so saturating keeps the semantics
+        let offset = u32::try_from(offset).unwrap_or(u32::MAX);
+        let length = u32::try_from(length).unwrap_or(u32::MAX);
+
+        let read = unsafe { db_read_range(key_ptr, offset, length) };
+        if read == 0 {
+            // key does not exist in external storage
+            return None;
+        }
+
+        let value_ptr = read as *mut Region;
+        let data = unsafe { consume_region(value_ptr) };
+        Some(data)
+    }
+
     fn set(&mut self, key: &[u8], value: &[u8]) {
         if value.is_empty() {
             panic!("TL;DR: Value must not be empty in Storage::set but in most cases you can use Storage::remove instead. Long story: Getting empty values from storage is not well supported at the moment. Some of our internal interfaces cannot differentiate between a non-existent key and an empty value. Right now, you cannot rely on the behaviour of empty values. To protect you from trouble later on, we stop here. Sorry for the inconvenience! We highly welcome you to contribute to us, making this more solid one way or the other.");
@@ -6,7 +6,9 @@ mod addresses;
 mod assertions;
 mod binary;
 mod coin;
+mod contract_version;
 mod conversion;
+mod ct_compare;
 mod deps;
 mod errors;
 mod hex_binary;
@@ -15,12 +17,15 @@ mod import_helpers;
 #[cfg(feature = "iterator")]
 mod iterator;
 mod math;
+mod max_depth_ser;
+mod no_maps_ser;
 mod panic;
 mod query;
 mod results;
 mod sections;
 mod serde;
 mod storage;
+mod strict_de;
 mod timestamp;
 mod traits;
 mod types;
@@ -29,11 +34,15 @@ mod uuid;
 pub use crate::addresses::{Addr, CanonicalAddr};
 pub use crate::binary::Binary;
 pub use crate::coin::{coin, coins, has_coins, Coin};
+pub use crate::contract_version::{
+    ensure_contract_name, get_contract_version, set_contract_version, ContractVersion,
+};
+pub use crate::ct_compare::ct_compare;
 pub use crate::deps::{Deps, DepsMut, OwnedDeps};
 pub use crate::errors::{
     CheckedFromRatioError, CheckedMultiplyRatioError, ConversionOverflowError, DivideByZeroError,
     OverflowError, OverflowOperation, RecoverPubkeyError, StdError, StdResult, SystemError,
-    VerificationError,
+    UuidError, VerificationError,
 };
 pub use crate::hex_binary::HexBinary;
 #[cfg(feature = "stargate")]
@@ -62,6 +71,8 @@ pub use crate::query::{
 };
 #[cfg(feature = "stargate")]
 pub use crate::query::{ChannelResponse, IbcQuery, ListChannelsResponse, PortIdResponse};
+#[cfg(feature = "cosmwasm_1_3")]
+pub use crate::query::{DenomMetadata, DenomMetadataResponse, DenomUnit};
 #[allow(deprecated)]
 pub use crate::results::SubMsgExecutionResponse;
 pub use crate::results::{
@@ -69,11 +80,16 @@ pub use crate::results::{
     Empty, Event, QueryResponse, Reply, ReplyOn, Response, SubMsg, SubMsgResponse, SubMsgResult,
     SystemResult, WasmMsg,
 };
+#[cfg(feature = "stargate")]
+pub use crate::results::{
+    parse_instantiate_response_data, GovMsg, MsgInstantiateContractResponse, VoteOption,
+};
 #[cfg(feature = "staking")]
 pub use crate::results::{DistributionMsg, StakingMsg};
-#[cfg(feature = "stargate")]
-pub use crate::results::{GovMsg, VoteOption};
-pub use crate::serde::{from_binary, from_slice, to_binary, to_vec};
+pub use crate::serde::{
+    from_binary, from_slice, from_slice_strict, to_binary, to_query_response, to_vec,
+    to_vec_with_max_depth,
+};
 pub use crate::storage::MemoryStorage;
 pub use crate::timestamp::Timestamp;
 pub use crate::traits::{Api, Querier, QuerierResult, QuerierWrapper, Storage};
@@ -90,7 +106,9 @@ mod imports;
 mod memory; // Used by exports and imports only. This assumes pointers are 32 bit long, which makes it untestable on dev machines.
 
 #[cfg(target_arch = "wasm32")]
-pub use crate::exports::{do_execute, do_instantiate, do_migrate, do_query, do_reply, do_sudo};
+pub use crate::exports::{
+    do_execute, do_instantiate, do_migrate, do_migrate_with_info, do_query, do_reply, do_sudo,
+};
 #[cfg(all(feature = "stargate", target_arch = "wasm32"))]
 pub use crate::exports::{
     do_ibc_channel_close, do_ibc_channel_connect, do_ibc_channel_open, do_ibc_packet_ack,
@@ -106,4 +124,4 @@ pub mod testing;
 
 // Re-exports
 
-pub use cosmwasm_derive::{entry_point, IntoEvent};
+pub use cosmwasm_derive::{entry_point, IntoAttributes, IntoEvent};
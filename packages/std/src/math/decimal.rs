@@ -1553,6 +1553,22 @@ mod tests {
         assert!(Decimal::percent(1000).sqrt() < Decimal::percent(317));
     }
 
+    #[test]
+    fn decimal_uint128_sqrt_works_for_edge_cases() {
+        assert_eq!(Decimal::zero().sqrt(), Decimal::zero());
+        assert_eq!(Decimal::one().sqrt(), Decimal::one());
+        // 0.000000000000000001 has the exact root 0.000000001
+        assert_eq!(
+            Decimal::from_atomics(1u128, 18).unwrap().sqrt(),
+            Decimal::from_atomics(1u128, 9).unwrap()
+        );
+        // The lowest precision is used for MAX, but it does not overflow
+        assert_eq!(
+            Decimal::MAX.sqrt(),
+            Decimal::from_str("18446744073.709551615").unwrap()
+        );
+    }
+
     /// sqrt(2) is an irrational number, i.e. all 18 decimal places should be used.
     #[test]
     fn decimal_uint128_sqrt_is_precise() {
@@ -81,6 +81,24 @@ mod tests {
     #[test]
     fn isqrt_uint128() {
         assert_eq!(Uint128::new(24).isqrt(), Uint128::new(4));
+        assert_eq!(Uint128::zero().isqrt(), Uint128::zero());
+        assert_eq!(Uint128::one().isqrt(), Uint128::one());
+    }
+
+    #[test]
+    fn isqrt_uint128_is_exact_for_perfect_squares() {
+        for root in [2u128, 3, 10, 1_000_000_007, u64::MAX as u128] {
+            let square = Uint128::new(root * root);
+            assert_eq!(square.isqrt(), Uint128::new(root));
+            assert_eq!((square - Uint128::one()).isqrt(), Uint128::new(root - 1));
+        }
+    }
+
+    #[test]
+    fn isqrt_uint128_does_not_overflow() {
+        let max_root = Uint128::new(u64::MAX as u128);
+        assert_eq!(Uint128::MAX.isqrt(), max_root);
+        assert_eq!((Uint128::MAX - Uint128::one()).isqrt(), max_root);
     }
 
     #[test]
@@ -0,0 +1,366 @@
+//! A serializer wrapper that fails with an error when the JSON output would contain objects
+//! or arrays nested deeper than a given limit.
+//!
+//! The depth is counted while descending into the value, so serialization stops at the first
+//! container that exceeds the limit instead of recursing through the whole value. This is what
+//! protects the stack when serializing deeply nested data.
+//!
+//! The levels are counted the way serde-json-wasm writes them: sequences, tuples, structs and
+//! maps open one level, newtype variants one (`{"variant":value}`) and tuple and struct variants
+//! two (`{"variant":[...]}` and `{"variant":{...}}`). Options and newtype structs are transparent.
+
+use serde::ser::{
+    self, Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+/// A reference to a value that is serialized with a nesting depth limit.
+pub(crate) struct MaxDepth<'a, T: ?Sized> {
+    value: &'a T,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'a, T: ?Sized> MaxDepth<'a, T> {
+    pub(crate) fn new(value: &'a T, max_depth: usize) -> Self {
+        MaxDepth {
+            value,
+            depth: 0,
+            max_depth,
+        }
+    }
+}
+
+impl<T: Serialize + ?Sized> Serialize for MaxDepth<'_, T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(Wrapper {
+            inner: serializer,
+            depth: self.depth,
+            max_depth: self.max_depth,
+        })
+    }
+}
+
+/// Wraps serializers and compound serializers, forwarding to the inner value.
+/// `depth` is the nesting depth of the values serialized by this wrapper.
+struct Wrapper<S> {
+    inner: S,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<S> Wrapper<S> {
+    /// Returns the depth after opening the given number of levels or an error
+    /// if this exceeds the limit
+    fn enter<E: ser::Error>(&self, levels: usize) -> Result<usize, E> {
+        let depth = self.depth + levels;
+        if depth > self.max_depth {
+            return Err(E::custom(format!(
+                "Nesting depth exceeds the maximum of {}",
+                self.max_depth
+            )));
+        }
+        Ok(depth)
+    }
+
+    /// Wraps a value at the given depth
+    fn nested<'a, T: ?Sized>(&self, value: &'a T, depth: usize) -> MaxDepth<'a, T> {
+        MaxDepth {
+            value,
+            depth,
+            max_depth: self.max_depth,
+        }
+    }
+}
+
+macro_rules! forward_serialize {
+    ($($method:ident($($arg:ident: $ty:ty),*)),* $(,)?) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<Self::Ok, Self::Error> {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<S: Serializer> Serializer for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = Wrapper<S::SerializeSeq>;
+    type SerializeTuple = Wrapper<S::SerializeTuple>;
+    type SerializeTupleStruct = Wrapper<S::SerializeTupleStruct>;
+    type SerializeTupleVariant = Wrapper<S::SerializeTupleVariant>;
+    type SerializeMap = Wrapper<S::SerializeMap>;
+    type SerializeStruct = Wrapper<S::SerializeStruct>;
+    type SerializeStructVariant = Wrapper<S::SerializeStructVariant>;
+
+    forward_serialize! {
+        serialize_bool(v: bool),
+        serialize_i8(v: i8),
+        serialize_i16(v: i16),
+        serialize_i32(v: i32),
+        serialize_i64(v: i64),
+        serialize_i128(v: i128),
+        serialize_u8(v: u8),
+        serialize_u16(v: u16),
+        serialize_u32(v: u32),
+        serialize_u64(v: u64),
+        serialize_u128(v: u128),
+        serialize_f32(v: f32),
+        serialize_f64(v: f64),
+        serialize_char(v: char),
+        serialize_str(v: &str),
+        serialize_bytes(v: &[u8]),
+        serialize_none(),
+        serialize_unit(),
+        serialize_unit_struct(name: &'static str),
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str),
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_some(&value)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_newtype_struct(name, &value)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        let depth = self.enter(1)?;
+        let value = self.nested(value, depth);
+        self.inner
+            .serialize_newtype_variant(name, variant_index, variant, &value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let depth = self.enter(1)?;
+        let inner = self.inner.serialize_seq(len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        let depth = self.enter(1)?;
+        let inner = self.inner.serialize_tuple(len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let depth = self.enter(1)?;
+        let inner = self.inner.serialize_tuple_struct(name, len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let depth = self.enter(2)?;
+        let inner = self
+            .inner
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let depth = self.enter(1)?;
+        let inner = self.inner.serialize_map(len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let depth = self.enter(1)?;
+        let inner = self.inner.serialize_struct(name, len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let depth = self.enter(2)?;
+        let inner = self
+            .inner
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        Ok(Wrapper {
+            inner,
+            depth,
+            max_depth: self.max_depth,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+impl<S: SerializeSeq> SerializeSeq for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTuple> SerializeTuple for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_element(&value)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTupleStruct> SerializeTupleStruct for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeTupleVariant> SerializeTupleVariant for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_field(&value)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeMap> SerializeMap for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), S::Error> {
+        let key = self.nested(key, self.depth);
+        self.inner.serialize_key(&key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_value(&value)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeStruct> SerializeStruct for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_field(key, &value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
+
+impl<S: SerializeStructVariant> SerializeStructVariant for Wrapper<S> {
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), S::Error> {
+        let value = self.nested(value, self.depth);
+        self.inner.serialize_field(key, &value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), S::Error> {
+        self.inner.skip_field(key)
+    }
+
+    fn end(self) -> Result<S::Ok, S::Error> {
+        self.inner.end()
+    }
+}
@@ -19,6 +19,11 @@ pub enum BankQuery {
     /// Note that this may be much more expensive than Balance and should be avoided if possible.
     /// Return value is AllBalanceResponse.
     AllBalances { address: String },
+    /// This calls into the native bank module for querying the metadata of one denomination,
+    /// e.g. to display amounts with the right number of decimals.
+    /// Return value is DenomMetadataResponse.
+    #[cfg(feature = "cosmwasm_1_3")]
+    DenomMetadata { denom: String },
 }
 
 #[cfg(feature = "cosmwasm_1_1")]
@@ -45,3 +50,41 @@ pub struct AllBalanceResponse {
     /// Returns all non-zero coins held by this account.
     pub amount: Vec<Coin>,
 }
+
+#[cfg(feature = "cosmwasm_1_3")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub struct DenomMetadataResponse {
+    /// The metadata of the requested denom
+    pub metadata: DenomMetadata,
+}
+
+/// The metadata of a denomination as stored in Cosmos SDK's bank module
+#[cfg(feature = "cosmwasm_1_3")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct DenomMetadata {
+    pub description: String,
+    /// All units of this denom, including the base unit with exponent 0
+    pub denom_units: Vec<DenomUnit>,
+    /// The base denom, i.e. the smallest unit, which is used for all amounts on chain
+    pub base: String,
+    /// The suggested denom to display to users, e.g. ATOM
+    pub display: String,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub uri_hash: String,
+}
+
+/// One unit of a denomination, e.g. `uatom` with exponent 0 or `atom` with exponent 6
+#[cfg(feature = "cosmwasm_1_3")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DenomUnit {
+    pub denom: String,
+    /// The power of 10 this unit is worth in base units, i.e. the number of decimals
+    pub exponent: u32,
+    pub aliases: Vec<String>,
+}
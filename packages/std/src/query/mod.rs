@@ -13,6 +13,8 @@ mod wasm;
 #[cfg(feature = "cosmwasm_1_1")]
 pub use bank::SupplyResponse;
 pub use bank::{AllBalanceResponse, BalanceResponse, BankQuery};
+#[cfg(feature = "cosmwasm_1_3")]
+pub use bank::{DenomMetadata, DenomMetadataResponse, DenomUnit};
 #[cfg(feature = "stargate")]
 pub use ibc::{ChannelResponse, IbcQuery, ListChannelsResponse, PortIdResponse};
 #[cfg(feature = "staking")]
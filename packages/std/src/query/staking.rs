@@ -13,6 +13,17 @@ pub enum StakingQuery {
     BondedDenom {},
     /// AllDelegations will return all delegations by the delegator
     AllDelegations { delegator: String },
+    /// Like AllDelegations, but returns at most `limit` delegations to validators with an address
+    /// greater than `start_after`, ordered by validator address. Unset fields do not restrict
+    /// the result.
+    ///
+    /// The query response type is `AllDelegationsResponse`.
+    #[cfg(feature = "cosmwasm_1_3")]
+    AllDelegationsPaginated {
+        delegator: String,
+        limit: Option<u32>,
+        start_after: Option<String>,
+    },
     /// Delegation will return more detailed info on a particular
     /// delegation, defined by delegator/validator pair
     Delegation {
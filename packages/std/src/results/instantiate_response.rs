@@ -0,0 +1,191 @@
+#![cfg(feature = "stargate")]
+
+use crate::{Binary, StdError, StdResult};
+
+const TYPE_NAME: &str = "MsgInstantiateContractResponse";
+
+/// The response of a `MsgInstantiateContract`, which the chain puts into the `data`
+/// field of a submessage reply when instantiating a contract.
+///
+/// See [tx.proto](https://github.com/Finschia/finschia-sdk/blob/v0.46.0/proto/cosmwasm/wasm/v1/tx.proto).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsgInstantiateContractResponse {
+    /// The address of the new contract
+    pub contract_address: String,
+    /// The data returned by the new contract's instantiate entry point.
+    /// `None` if the contract did not set any data.
+    pub data: Option<Binary>,
+}
+
+/// Decodes the protobuf encoded `MsgInstantiateContractResponse` from the data of an
+/// instantiate submessage reply.
+///
+/// Unknown fields are skipped.
+pub fn parse_instantiate_response_data(data: &Binary) -> StdResult<MsgInstantiateContractResponse> {
+    let mut reader = ProtoReader {
+        data: data.as_slice(),
+    };
+    let mut contract_address = String::new();
+    let mut response_data = Vec::<u8>::new();
+
+    while !reader.is_empty() {
+        let key = reader.read_varint()?;
+        let field_number = key >> 3;
+        let wire_type = key & 0x07;
+        match (field_number, wire_type) {
+            (1, 2) => {
+                let bytes = reader.read_length_delimited()?;
+                contract_address = String::from_utf8(bytes.to_vec())
+                    .map_err(|_| StdError::parse_err(TYPE_NAME, "contract_address is not UTF-8"))?;
+            }
+            (2, 2) => {
+                response_data = reader.read_length_delimited()?.to_vec();
+            }
+            (1 | 2, _) => {
+                return Err(StdError::parse_err(
+                    TYPE_NAME,
+                    format!("invalid wire type {} for field {}", wire_type, field_number),
+                ))
+            }
+            _ => reader.skip(wire_type)?,
+        }
+    }
+
+    Ok(MsgInstantiateContractResponse {
+        contract_address,
+        data: if response_data.is_empty() {
+            None
+        } else {
+            Some(Binary(response_data))
+        },
+    })
+}
+
+/// A minimal reader for the protobuf wire format
+struct ProtoReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ProtoReader<'a> {
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> StdResult<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(StdError::parse_err(TYPE_NAME, "unexpected end of data"));
+        }
+        let (out, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(out)
+    }
+
+    fn read_varint(&mut self) -> StdResult<u64> {
+        let mut value = 0u64;
+        // A u64 varint has at most 10 bytes
+        for i in 0..10 {
+            let byte = self.read_bytes(1)?[0];
+            value |= u64::from(byte & 0x7f) << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(StdError::parse_err(TYPE_NAME, "varint too long"))
+    }
+
+    fn read_length_delimited(&mut self) -> StdResult<&'a [u8]> {
+        let len = self.read_varint()?;
+        let len = usize::try_from(len)
+            .map_err(|_| StdError::parse_err(TYPE_NAME, "length out of range"))?;
+        self.read_bytes(len)
+    }
+
+    fn skip(&mut self, wire_type: u64) -> StdResult<()> {
+        match wire_type {
+            0 => self.read_varint().map(|_| ()),
+            1 => self.read_bytes(8).map(|_| ()),
+            2 => self.read_length_delimited().map(|_| ()),
+            5 => self.read_bytes(4).map(|_| ()),
+            _ => Err(StdError::parse_err(
+                TYPE_NAME,
+                format!("unsupported wire type {}", wire_type),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "link14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9sgf2vn8";
+
+    #[test]
+    fn parse_instantiate_response_data_works() {
+        // contract_address and data
+        let encoded = Binary::from_base64("Cj9saW5rMTRoajJ0YXZxOGZwZXNkd3h4Y3U0NHJ0eTNoaDkwdmh1anJ2Y21zdGw0enIzdHhtZnZ3OXNnZjJ2bjgSC3sib2siOnRydWV9").unwrap();
+        let response = parse_instantiate_response_data(&encoded).unwrap();
+        assert_eq!(
+            response,
+            MsgInstantiateContractResponse {
+                contract_address: ADDRESS.to_string(),
+                data: Some(Binary::from(br#"{"ok":true}"#)),
+            }
+        );
+
+        // contract_address only
+        let encoded = Binary::from(&encoded.as_slice()[..65]);
+        let response = parse_instantiate_response_data(&encoded).unwrap();
+        assert_eq!(
+            response,
+            MsgInstantiateContractResponse {
+                contract_address: ADDRESS.to_string(),
+                data: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_instantiate_response_data_skips_unknown_fields() {
+        let mut encoded = vec![0x18, 0x96, 0x01]; // field 3, varint 150
+        encoded.extend_from_slice(&[0x0a, 0x04]);
+        encoded.extend_from_slice(b"addr");
+        encoded.extend_from_slice(&[0x25, 0x01, 0x02, 0x03, 0x04]); // field 4, fixed32
+        let response = parse_instantiate_response_data(&Binary(encoded)).unwrap();
+        assert_eq!(response.contract_address, "addr");
+        assert_eq!(response.data, None);
+    }
+
+    #[test]
+    fn parse_instantiate_response_data_fails_for_invalid_data() {
+        // truncated
+        let encoded = Binary::from([0x0a, 0x05, b'a', b'd']);
+        match parse_instantiate_response_data(&encoded).unwrap_err() {
+            StdError::ParseErr {
+                target_type, msg, ..
+            } => {
+                assert_eq!(target_type, "MsgInstantiateContractResponse");
+                assert_eq!(msg, "unexpected end of data");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // wrong wire type for contract_address
+        let encoded = Binary::from([0x08, 0x01]);
+        match parse_instantiate_response_data(&encoded).unwrap_err() {
+            StdError::ParseErr { msg, .. } => {
+                assert_eq!(msg, "invalid wire type 0 for field 1")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // invalid UTF-8
+        let encoded = Binary::from([0x0a, 0x01, 0xff]);
+        match parse_instantiate_response_data(&encoded).unwrap_err() {
+            StdError::ParseErr { msg, .. } => {
+                assert_eq!(msg, "contract_address is not UTF-8")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
@@ -4,6 +4,7 @@ mod contract_result;
 mod cosmos_msg;
 mod empty;
 mod events;
+mod instantiate_response;
 mod query;
 mod response;
 mod submessages;
@@ -17,6 +18,8 @@ pub use cosmos_msg::{DistributionMsg, StakingMsg};
 pub use cosmos_msg::{GovMsg, VoteOption};
 pub use empty::Empty;
 pub use events::{attr, Attribute, Event};
+#[cfg(feature = "stargate")]
+pub use instantiate_response::{parse_instantiate_response_data, MsgInstantiateContractResponse};
 pub use query::QueryResponse;
 pub use response::Response;
 #[allow(deprecated)]
@@ -7,20 +7,37 @@ use std::any::type_name;
 
 use crate::binary::Binary;
 use crate::errors::{StdError, StdResult};
+use crate::max_depth_ser::MaxDepth;
+use crate::no_maps_ser::NoMaps;
+use crate::results::QueryResponse;
+use crate::strict_de::DenyUnknownFields;
 
 pub fn from_slice<T: DeserializeOwned>(value: &[u8]) -> StdResult<T> {
     serde_json_wasm::from_slice(value).map_err(|e| StdError::parse_err(type_name::<T>(), e))
 }
 
+/// Like [`from_slice`] but returns an error if the JSON contains fields that are not known
+/// to `T`, as if all involved types were annotated with `#[serde(deny_unknown_fields)]`.
+pub fn from_slice_strict<T: DeserializeOwned>(value: &[u8]) -> StdResult<T> {
+    serde_json_wasm::from_slice::<DenyUnknownFields<T>>(value)
+        .map(|DenyUnknownFields(value)| value)
+        .map_err(|e| StdError::parse_err(type_name::<T>(), e))
+}
+
 pub fn from_binary<T: DeserializeOwned>(value: &Binary) -> StdResult<T> {
     from_slice(value.as_slice())
 }
 
+/// Serializes the given data to JSON.
+///
+/// Maps (e.g. `HashMap`, `BTreeMap` or `#[serde(flatten)]`) are not supported and result in an
+/// error. This keeps the output deterministic, which matters for consensus, since the iteration
+/// order of a `HashMap` is unspecified. Use structs or sorted lists of key/value pairs instead.
 pub fn to_vec<T>(data: &T) -> StdResult<Vec<u8>>
 where
     T: Serialize + ?Sized,
 {
-    serde_json_wasm::to_vec(data).map_err(|e| StdError::serialize_err(type_name::<T>(), e))
+    serde_json_wasm::to_vec(&NoMaps(data)).map_err(|e| StdError::serialize_err(type_name::<T>(), e))
 }
 
 pub fn to_binary<T>(data: &T) -> StdResult<Binary>
@@ -30,6 +47,28 @@ where
     to_vec(data).map(Binary)
 }
 
+/// Serializes the given data into a query response. This is the same as [`to_binary`]
+/// but reads better at the end of a query handler.
+pub fn to_query_response<T>(data: &T) -> StdResult<QueryResponse>
+where
+    T: Serialize + ?Sized,
+{
+    to_binary(data)
+}
+
+/// Like [`to_vec`] but returns an error if the serialized data contains objects or
+/// arrays that are nested deeper than `max_depth`.
+///
+/// The depth is checked while serializing, such that deeply nested values are rejected
+/// before they are fully traversed.
+pub fn to_vec_with_max_depth<T>(data: &T, max_depth: usize) -> StdResult<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json_wasm::to_vec(&MaxDepth::new(&NoMaps(data), max_depth))
+        .map_err(|e| StdError::serialize_err(type_name::<T>(), e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,6 +108,165 @@ mod tests {
         );
     }
 
+    #[test]
+    fn to_vec_fails_for_maps() {
+        use std::collections::{BTreeMap, HashMap};
+
+        #[derive(Serialize)]
+        struct WithMap {
+            balances: HashMap<String, u64>,
+        }
+
+        #[derive(Serialize)]
+        enum WithNestedMap {
+            Update {
+                entries: Vec<Option<BTreeMap<String, u64>>>,
+            },
+        }
+
+        let balances: HashMap<String, u64> = [("alice".to_string(), 1), ("bob".to_string(), 2)]
+            .into_iter()
+            .collect();
+        let msg = WithMap { balances };
+        match to_vec(&msg).unwrap_err() {
+            StdError::SerializeErr { msg, .. } => assert!(
+                msg.contains("Maps are not supported"),
+                "Unexpected message: {}",
+                msg
+            ),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        let entries = vec![None, Some(BTreeMap::from([("carl".to_string(), 3)]))];
+        let msg = WithNestedMap::Update { entries };
+        match to_vec(&msg).unwrap_err() {
+            StdError::SerializeErr { msg, .. } => assert!(
+                msg.contains("Maps are not supported"),
+                "Unexpected message: {}",
+                msg
+            ),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // without a map the output is unaffected
+        let msg = WithNestedMap::Update {
+            entries: vec![None],
+        };
+        assert_eq!(to_vec(&msg).unwrap(), br#"{"Update":{"entries":[null]}}"#);
+    }
+
+    #[derive(Serialize, Debug)]
+    struct Node {
+        text: String,
+        child: Option<Box<Node>>,
+    }
+
+    fn nested(depth: usize) -> Node {
+        let mut node = Node {
+            text: "{[leaf]}".to_string(),
+            child: None,
+        };
+        for _ in 1..depth {
+            node = Node {
+                text: "\\\"{".to_string(),
+                child: Some(Box::new(node)),
+            };
+        }
+        node
+    }
+
+    #[test]
+    fn to_vec_with_max_depth_works() {
+        let msg = SomeMsg::Refund {};
+        let serialized = to_vec_with_max_depth(&msg, 2).unwrap();
+        assert_eq!(serialized, br#"{"refund":{}}"#);
+
+        // brackets in strings do not count
+        let serialized = to_vec_with_max_depth(&nested(10), 10).unwrap();
+        assert_eq!(serialized, to_vec(&nested(10)).unwrap());
+    }
+
+    #[test]
+    fn to_vec_with_max_depth_errors_when_nested_too_deep() {
+        let msg = SomeMsg::Refund {};
+        match to_vec_with_max_depth(&msg, 1).unwrap_err() {
+            StdError::SerializeErr { msg, .. } => {
+                assert_eq!(msg, "Nesting depth exceeds the maximum of 1")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        match to_vec_with_max_depth(&nested(129), 128).unwrap_err() {
+            StdError::SerializeErr {
+                source_type, msg, ..
+            } => {
+                assert_eq!(source_type, "cosmwasm_std::serde::tests::Node");
+                assert_eq!(msg, "Nesting depth exceeds the maximum of 128");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    /// A value that is nested infinitely deep
+    struct Infinite;
+
+    impl Serialize for Infinite {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeSeq;
+            let mut seq = serializer.serialize_seq(Some(1))?;
+            seq.serialize_element(&Infinite)?;
+            seq.end()
+        }
+    }
+
+    #[test]
+    fn to_vec_with_max_depth_stops_early() {
+        // This would exhaust the stack if the depth was checked after serialization
+        match to_vec_with_max_depth(&Infinite, 32).unwrap_err() {
+            StdError::SerializeErr { msg, .. } => {
+                assert_eq!(msg, "Nesting depth exceeds the maximum of 32")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn to_vec_with_max_depth_counts_variants_and_rejects_maps() {
+        #[derive(Serialize)]
+        enum Variants {
+            Newtype(u8),
+            Tuple(u8, u8),
+            Struct { a: u8 },
+        }
+
+        assert_eq!(
+            to_vec_with_max_depth(&Variants::Newtype(1), 1).unwrap(),
+            br#"{"Newtype":1}"#
+        );
+        assert!(to_vec_with_max_depth(&Variants::Newtype(1), 0).is_err());
+        assert_eq!(
+            to_vec_with_max_depth(&Variants::Tuple(1, 2), 2).unwrap(),
+            br#"{"Tuple":[1,2]}"#
+        );
+        assert!(to_vec_with_max_depth(&Variants::Tuple(1, 2), 1).is_err());
+        assert_eq!(
+            to_vec_with_max_depth(&Variants::Struct { a: 1 }, 2).unwrap(),
+            br#"{"Struct":{"a":1}}"#
+        );
+        assert!(to_vec_with_max_depth(&Variants::Struct { a: 1 }, 1).is_err());
+
+        // options and newtype structs are transparent
+        assert_eq!(to_vec_with_max_depth(&Some(vec![1u8]), 1).unwrap(), b"[1]");
+
+        let map = std::collections::BTreeMap::from([(1u8, 2u8)]);
+        match to_vec_with_max_depth(&map, 10).unwrap_err() {
+            StdError::SerializeErr { msg, .. } => {
+                assert!(msg.contains("Maps are not supported"))
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn from_slice_works() {
         let deserialized: SomeMsg = from_slice(br#"{"refund":{}}"#).unwrap();
@@ -89,6 +287,79 @@ mod tests {
         );
     }
 
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Outer {
+        name: String,
+        inner: Option<Inner>,
+        items: Vec<Inner>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Inner {
+        value: u32,
+    }
+
+    #[test]
+    fn from_slice_strict_works() {
+        let deserialized: SomeMsg = from_slice_strict(
+            br#"{"release_all":{"image":"foo","amount":42,"time":18446744073709551615,"karma":-17}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            deserialized,
+            SomeMsg::ReleaseAll {
+                image: "foo".to_string(),
+                amount: 42,
+                time: 18446744073709551615,
+                karma: -17
+            }
+        );
+
+        let deserialized: Outer =
+            from_slice_strict(br#"{"name":"a","inner":{"value":1},"items":[{"value":2}]}"#)
+                .unwrap();
+        assert_eq!(
+            deserialized,
+            Outer {
+                name: "a".to_string(),
+                inner: Some(Inner { value: 1 }),
+                items: vec![Inner { value: 2 }],
+            }
+        );
+    }
+
+    #[test]
+    fn from_slice_strict_rejects_unknown_fields() {
+        let json = br#"{"cowsay":{"text":"moo","extra":{"nested":[1,2]}}}"#;
+
+        // lenient parsing ignores the unknown field
+        let deserialized: SomeMsg = from_slice(json).unwrap();
+        assert_eq!(
+            deserialized,
+            SomeMsg::Cowsay {
+                text: "moo".to_string()
+            }
+        );
+
+        match from_slice_strict::<SomeMsg>(json).unwrap_err() {
+            StdError::ParseErr {
+                target_type, msg, ..
+            } => {
+                assert_eq!(target_type, "cosmwasm_std::serde::tests::SomeMsg");
+                assert_eq!(msg, "unknown field `extra`");
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // unknown fields in nested values are rejected as well
+        let json = br#"{"name":"a","inner":null,"items":[{"value":2,"evil":true}]}"#;
+        assert!(from_slice::<Outer>(json).is_ok());
+        match from_slice_strict::<Outer>(json).unwrap_err() {
+            StdError::ParseErr { msg, .. } => assert_eq!(msg, "unknown field `evil`"),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn from_slice_or_binary() {
         let msg = SomeMsg::Refund {};
@@ -101,6 +372,19 @@ mod tests {
         assert_eq!(parse_slice, msg);
     }
 
+    #[test]
+    fn to_query_response_works() {
+        let msg = SomeMsg::Cowsay {
+            text: "hello".to_string(),
+        };
+        let response = to_query_response(&msg).unwrap();
+        assert_eq!(response, to_binary(&msg).unwrap());
+        assert_eq!(response.as_slice(), br#"{"cowsay":{"text":"hello"}}"#);
+
+        let parsed: SomeMsg = from_binary(&response).unwrap();
+        assert_eq!(parsed, msg);
+    }
+
     #[test]
     fn to_vec_works_for_special_chars() {
         let msg = SomeMsg::Cowsay {
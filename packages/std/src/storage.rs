@@ -119,6 +119,25 @@ mod tests {
         assert_eq!(store.get(b"food"), None);
     }
 
+    #[test]
+    fn get_range_works() {
+        let mut store = MemoryStorage::new();
+        store.set(b"foo", b"insect");
+        assert_eq!(store.get_range(b"foo", 0, 3), Some(b"ins".to_vec()));
+        assert_eq!(store.get_range(b"foo", 2, 3), Some(b"sec".to_vec()));
+        assert_eq!(store.get_range(b"foo", 4, 100), Some(b"ct".to_vec()));
+        assert_eq!(
+            store.get_range(b"foo", 0, usize::MAX),
+            Some(b"insect".to_vec())
+        );
+        assert_eq!(store.get_range(b"foo", 6, 1), Some(vec![]));
+        assert_eq!(
+            store.get_range(b"foo", usize::MAX, usize::MAX),
+            Some(vec![])
+        );
+        assert_eq!(store.get_range(b"food", 0, 3), None);
+    }
+
     #[test]
     #[should_panic(
         expected = "Getting empty values from storage is not well supported at the moment."
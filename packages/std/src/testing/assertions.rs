@@ -1,4 +1,5 @@
-use crate::{Decimal, Uint128};
+use crate::{Decimal, Response, Uint128};
+use std::fmt::Debug;
 use std::str::FromStr as _;
 
 /// Asserts that two expressions are approximately equal to each other.
@@ -52,8 +53,54 @@ pub fn assert_approx_eq_impl<U: Into<Uint128>>(
     }
 }
 
+/// Asserts that the response contains no messages.
+///
+/// On panic, the messages of the response are printed.
+#[track_caller]
+pub fn assert_no_messages<T: Debug>(response: &Response<T>) {
+    if !response.messages.is_empty() {
+        panic!(
+            "assertion failed: response contains no messages\nactual messages: {:?}",
+            response.messages
+        );
+    }
+}
+
+/// Asserts that the response contains an attribute with the given key and value.
+///
+/// On panic, the attributes of the response are printed.
+#[track_caller]
+pub fn assert_attribute<T>(response: &Response<T>, key: &str, value: &str) {
+    if !response
+        .attributes
+        .iter()
+        .any(|attr| attr.key == key && attr.value == value)
+    {
+        panic!(
+            "assertion failed: response contains attribute `{}` with value `{}`\nactual attributes: {:?}",
+            key, value, response.attributes
+        );
+    }
+}
+
+/// Asserts that the response contains an event of the given type.
+///
+/// On panic, the events of the response are printed.
+#[track_caller]
+pub fn assert_event<T>(response: &Response<T>, ty: &str) {
+    if !response.events.iter().any(|event| event.ty == ty) {
+        panic!(
+            "assertion failed: response contains event of type `{}`\nactual events: {:?}",
+            ty, response.events
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::{BankMsg, Event};
+
     #[test]
     fn assert_approx() {
         assert_approx_eq!(9_u32, 10_u32, "0.12");
@@ -105,4 +152,58 @@ mod tests {
             Foo(8),
         );
     }
+
+    fn response() -> Response {
+        Response::new()
+            .add_attribute("action", "release")
+            .add_event(Event::new("transfer").add_attribute("amount", "12"))
+    }
+
+    #[test]
+    fn assert_no_messages_works() {
+        assert_no_messages(&response());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: response contains no messages\nactual messages: [SubMsg { id: 0, msg: Bank(Burn { amount: [] }), gas_limit: None, reply_on: Never }]"
+    )]
+    fn assert_no_messages_fails_for_messages() {
+        let response = response().add_message(BankMsg::Burn { amount: vec![] });
+        assert_no_messages(&response);
+    }
+
+    #[test]
+    fn assert_attribute_works() {
+        assert_attribute(&response(), "action", "release");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: response contains attribute `action` with value `burn`\nactual attributes: [Attribute { key: \"action\", value: \"release\" }]"
+    )]
+    fn assert_attribute_fails_for_different_value() {
+        assert_attribute(&response(), "action", "burn");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: response contains attribute `amount` with value `12`"
+    )]
+    fn assert_attribute_ignores_event_attributes() {
+        assert_attribute(&response(), "amount", "12");
+    }
+
+    #[test]
+    fn assert_event_works() {
+        assert_event(&response(), "transfer");
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "assertion failed: response contains event of type `mint`\nactual events: [Event { ty: \"transfer\", attributes: [Attribute { key: \"amount\", value: \"12\" }] }]"
+    )]
+    fn assert_event_fails_for_missing_event() {
+        assert_event(&response(), "mint");
+    }
 }
@@ -3,6 +3,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use thiserror::Error;
 
 use crate::addresses::{Addr, CanonicalAddr};
 use crate::binary::Binary;
@@ -28,6 +29,8 @@ use crate::query::{
     AllDelegationsResponse, AllValidatorsResponse, BondedDenomResponse, DelegationResponse,
     FullDelegation, StakingQuery, Validator, ValidatorResponse,
 };
+#[cfg(feature = "cosmwasm_1_3")]
+use crate::query::{DenomMetadata, DenomMetadataResponse};
 use crate::results::{ContractResult, Empty, SystemResult};
 use crate::serde::{from_slice, to_binary};
 use crate::storage::MemoryStorage;
@@ -38,6 +41,13 @@ use crate::Attribute;
 
 pub const MOCK_CONTRACT_ADDR: &str = "cosmos2contract";
 
+/// The block height of [`mock_env`]
+pub const FIXED_BLOCK_HEIGHT: u64 = 12_345;
+/// The block time of [`mock_env`], which is 2019-10-23T02:23:39.879305533Z
+pub const FIXED_BLOCK_TIME: Timestamp = Timestamp::from_nanos(1_571_797_419_879_305_533);
+/// The chain ID of [`mock_env`]
+pub const FIXED_CHAIN_ID: &str = "cosmos-testnet-14002";
+
 /// Creates all external requirements that can be injected for unit tests.
 ///
 /// See also [`mock_dependencies_with_balance`] and [`mock_dependencies_with_balances`]
@@ -229,13 +239,22 @@ impl Api for MockApi {
 /// You can submit as is to most contracts, or modify height/time if you want to
 /// test for expiration.
 ///
+/// The block is always [`FIXED_BLOCK_HEIGHT`], [`FIXED_BLOCK_TIME`] and [`FIXED_CHAIN_ID`].
+///
 /// This is intended for use in test code only.
 pub fn mock_env() -> Env {
+    mock_env_at(FIXED_BLOCK_HEIGHT, FIXED_BLOCK_TIME)
+}
+
+/// Like [`mock_env`] but with the given block height and time.
+///
+/// This is intended for use in test code only.
+pub fn mock_env_at(height: u64, time: Timestamp) -> Env {
     Env {
         block: BlockInfo {
-            height: 12_345,
-            time: Timestamp::from_nanos(1_571_797_419_879_305_533),
-            chain_id: "cosmos-testnet-14002".to_string(),
+            height,
+            time,
+            chain_id: FIXED_CHAIN_ID.to_string(),
         },
         transaction: Some(TransactionInfo { index: 3 }),
         contract: ContractInfo {
@@ -464,6 +483,16 @@ impl<C: DeserializeOwned> MockQuerier<C> {
         self.bank.update_balance(addr, balance)
     }
 
+    /// Sets new balances for all given addresses at once. Balances of other addresses are kept.
+    pub fn update_balances(&mut self, entries: &[(&str, Vec<Coin>)]) {
+        self.bank.update_balances(entries)
+    }
+
+    /// Removes the balances of all addresses
+    pub fn clear_balances(&mut self) {
+        self.bank.clear_balances()
+    }
+
     #[cfg(feature = "staking")]
     pub fn update_staking(
         &mut self,
@@ -481,6 +510,31 @@ impl<C: DeserializeOwned> MockQuerier<C> {
         self.wasm.update_handler(handler)
     }
 
+    /// Sets the raw storage of the given contract, replacing any state set before.
+    ///
+    /// `WasmQuery::Raw` requests for this contract are answered from this state and
+    /// return an empty value for missing keys. All other Wasm queries are passed
+    /// to the handler set via `update_wasm`.
+    pub fn update_contract_state(
+        &mut self,
+        contract_addr: impl Into<String>,
+        state: &[(&[u8], &[u8])],
+    ) {
+        self.wasm.update_contract_state(contract_addr, state)
+    }
+
+    /// Sets the metadata returned for `BankQuery::DenomMetadata` requests for the given denom
+    #[cfg(feature = "cosmwasm_1_3")]
+    #[must_use]
+    pub fn with_denom_metadata(
+        mut self,
+        denom: impl Into<String>,
+        metadata: DenomMetadata,
+    ) -> Self {
+        self.bank.set_denom_metadata(denom, metadata);
+        self
+    }
+
     #[must_use]
     pub fn with_custom_handler<CH: 'static>(mut self, handler: CH) -> Self
     where
@@ -538,11 +592,28 @@ struct WasmQuerier {
     ///
     /// Use box to avoid the need of generic type.
     handler: Box<dyn for<'a> Fn(&'a WasmQuery) -> QuerierResult>,
+    /// HashMap<contract address, raw storage>
+    contract_states: HashMap<String, HashMap<Vec<u8>, Vec<u8>>>,
 }
 
 impl WasmQuerier {
     fn new(handler: Box<dyn for<'a> Fn(&'a WasmQuery) -> QuerierResult>) -> Self {
-        Self { handler }
+        Self {
+            handler,
+            contract_states: HashMap::new(),
+        }
+    }
+
+    fn update_contract_state(
+        &mut self,
+        contract_addr: impl Into<String>,
+        state: &[(&[u8], &[u8])],
+    ) {
+        let state = state
+            .iter()
+            .map(|(key, value)| (key.to_vec(), value.to_vec()))
+            .collect();
+        self.contract_states.insert(contract_addr.into(), state);
     }
 
     fn update_handler<WH: 'static>(&mut self, handler: WH)
@@ -553,6 +624,12 @@ impl WasmQuerier {
     }
 
     fn query(&self, request: &WasmQuery) -> QuerierResult {
+        if let WasmQuery::Raw { contract_addr, key } = request {
+            if let Some(state) = self.contract_states.get(contract_addr) {
+                let value = state.get(key.as_slice()).cloned().unwrap_or_default();
+                return SystemResult::Ok(ContractResult::Ok(Binary(value)));
+            }
+        }
         (*self.handler)(request)
     }
 }
@@ -579,6 +656,9 @@ pub struct BankQuerier {
     supplies: HashMap<String, Uint128>,
     /// HashMap<address, coins>
     balances: HashMap<String, Vec<Coin>>,
+    /// HashMap<denom, metadata>
+    #[cfg(feature = "cosmwasm_1_3")]
+    denom_metadata: HashMap<String, DenomMetadata>,
 }
 
 impl BankQuerier {
@@ -591,9 +671,21 @@ impl BankQuerier {
         BankQuerier {
             supplies: Self::calculate_supplies(&balances),
             balances,
+            #[cfg(feature = "cosmwasm_1_3")]
+            denom_metadata: HashMap::new(),
         }
     }
 
+    /// Sets the metadata for the given denom and returns the old one
+    #[cfg(feature = "cosmwasm_1_3")]
+    pub fn set_denom_metadata(
+        &mut self,
+        denom: impl Into<String>,
+        metadata: DenomMetadata,
+    ) -> Option<DenomMetadata> {
+        self.denom_metadata.insert(denom.into(), metadata)
+    }
+
     pub fn update_balance(
         &mut self,
         addr: impl Into<String>,
@@ -605,6 +697,18 @@ impl BankQuerier {
         result
     }
 
+    pub fn update_balances(&mut self, entries: &[(&str, Vec<Coin>)]) {
+        for (addr, balance) in entries {
+            self.balances.insert(addr.to_string(), balance.clone());
+        }
+        self.supplies = Self::calculate_supplies(&self.balances);
+    }
+
+    pub fn clear_balances(&mut self) {
+        self.balances.clear();
+        self.supplies.clear();
+    }
+
     fn calculate_supplies(balances: &HashMap<String, Vec<Coin>>) -> HashMap<String, Uint128> {
         let mut supplies = HashMap::new();
 
@@ -658,6 +762,17 @@ impl BankQuerier {
                 };
                 to_binary(&bank_res).into()
             }
+            #[cfg(feature = "cosmwasm_1_3")]
+            BankQuery::DenomMetadata { denom } => match self.denom_metadata.get(denom) {
+                Some(metadata) => {
+                    let bank_res = DenomMetadataResponse {
+                        metadata: metadata.clone(),
+                    };
+                    to_binary(&bank_res).into()
+                }
+                // like the bank module, which errors for unknown denoms
+                None => ContractResult::Err(format!("No metadata found for denom {}", denom)),
+            },
         };
         // system result is always ok in the mock implementation
         SystemResult::Ok(contract_result)
@@ -716,6 +831,32 @@ impl StakingQuerier {
                 let res = AllDelegationsResponse { delegations };
                 to_binary(&res).into()
             }
+            #[cfg(feature = "cosmwasm_1_3")]
+            StakingQuery::AllDelegationsPaginated {
+                delegator,
+                limit,
+                start_after,
+            } => {
+                let mut delegations: Vec<&FullDelegation> = self
+                    .delegations
+                    .iter()
+                    .filter(|d| d.delegator.as_str() == delegator)
+                    .filter(|d| match start_after {
+                        Some(start_after) => d.validator > *start_after,
+                        None => true,
+                    })
+                    .collect();
+                delegations.sort_by(|a, b| a.validator.cmp(&b.validator));
+                let limit = limit.map_or(delegations.len(), |limit| limit as usize);
+                let delegations = delegations
+                    .into_iter()
+                    .take(limit)
+                    .cloned()
+                    .map(|d| d.into())
+                    .collect();
+                let res = AllDelegationsResponse { delegations };
+                to_binary(&res).into()
+            }
             StakingQuery::Delegation {
                 delegator,
                 validator,
@@ -797,6 +938,23 @@ pub fn riffle_shuffle<T: Clone>(input: &[T]) -> Vec<T> {
     out
 }
 
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ShuffleError {
+    #[error("Riffle shuffle is only defined for an even number of elements, got {length}")]
+    OddLength { length: usize },
+}
+
+/// Like [`riffle_shuffle`] but returns an error instead of panicking
+/// if the input has an odd number of elements.
+pub fn try_riffle_shuffle<T: Clone>(input: &[T]) -> Result<Vec<T>, ShuffleError> {
+    if input.len() % 2 != 0 {
+        return Err(ShuffleError::OddLength {
+            length: input.len(),
+        });
+    }
+    Ok(riffle_shuffle(input))
+}
+
 pub fn digit_sum(input: &[u8]) -> usize {
     input.iter().fold(0, |sum, val| sum + (*val as usize))
 }
@@ -829,6 +987,30 @@ mod tests {
     const ED25519_PUBKEY_HEX: &str =
         "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c";
 
+    #[test]
+    fn mock_env_works() {
+        let env = mock_env();
+        assert_eq!(
+            env.block,
+            BlockInfo {
+                height: FIXED_BLOCK_HEIGHT,
+                time: FIXED_BLOCK_TIME,
+                chain_id: FIXED_CHAIN_ID.to_string(),
+            }
+        );
+        assert_eq!(env.block.height, 12_345);
+        assert_eq!(env.block.time.seconds(), 1_571_797_419);
+        assert_eq!(env.block.time.subsec_nanos(), 879_305_533);
+        assert_eq!(env.contract.address, MOCK_CONTRACT_ADDR);
+
+        let env = mock_env_at(FIXED_BLOCK_HEIGHT + 1, FIXED_BLOCK_TIME.plus_seconds(5));
+        assert_eq!(env.block.height, 12_346);
+        assert_eq!(env.block.time.seconds(), 1_571_797_424);
+        assert_eq!(env.block.time.subsec_nanos(), 879_305_533);
+        assert_eq!(env.block.chain_id, FIXED_CHAIN_ID);
+        assert_eq!(env.transaction, mock_env().transaction);
+    }
+
     #[test]
     fn mock_info_works() {
         let info = mock_info("my name", &coins(100, "atom"));
@@ -1158,6 +1340,116 @@ mod tests {
         assert_eq!(res.amount, coin(0, "ATOM"));
     }
 
+    #[cfg(feature = "cosmwasm_1_3")]
+    #[test]
+    fn bank_querier_denom_metadata() {
+        use crate::query::DenomUnit;
+
+        let metadata = DenomMetadata {
+            description: "The native staking token".to_string(),
+            denom_units: vec![
+                DenomUnit {
+                    denom: "ucosm".to_string(),
+                    exponent: 0,
+                    aliases: vec!["microcosm".to_string()],
+                },
+                DenomUnit {
+                    denom: "cosm".to_string(),
+                    exponent: 6,
+                    aliases: vec![],
+                },
+            ],
+            base: "ucosm".to_string(),
+            display: "cosm".to_string(),
+            name: "Cosm".to_string(),
+            symbol: "COSM".to_string(),
+            ..DenomMetadata::default()
+        };
+        let mut bank = BankQuerier::new(&[]);
+        assert_eq!(bank.set_denom_metadata("ucosm", metadata.clone()), None);
+
+        let res = bank
+            .query(&BankQuery::DenomMetadata {
+                denom: "ucosm".to_string(),
+            })
+            .unwrap()
+            .unwrap();
+        let res: DenomMetadataResponse = from_binary(&res).unwrap();
+        assert_eq!(res.metadata, metadata);
+
+        // unknown denom
+        let res = bank
+            .query(&BankQuery::DenomMetadata {
+                denom: "uatom".to_string(),
+            })
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(res, "No metadata found for denom uatom");
+    }
+
+    #[cfg(feature = "cosmwasm_1_3")]
+    #[test]
+    fn mock_querier_with_denom_metadata_works() {
+        use crate::QuerierWrapper;
+
+        let metadata = DenomMetadata {
+            base: "ucosm".to_string(),
+            display: "cosm".to_string(),
+            ..DenomMetadata::default()
+        };
+        let querier: MockQuerier =
+            MockQuerier::new(&[]).with_denom_metadata("ucosm", metadata.clone());
+        let wrapper = QuerierWrapper::<Empty>::new(&querier);
+
+        assert_eq!(wrapper.query_denom_metadata("ucosm").unwrap(), metadata);
+        match wrapper.query_denom_metadata("uatom").unwrap_err() {
+            StdError::GenericErr { msg, .. } => assert_eq!(
+                msg,
+                "Querier contract error: No metadata found for denom uatom"
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn mock_querier_update_balances_works() {
+        use crate::QuerierWrapper;
+
+        let mut querier: MockQuerier = MockQuerier::new(&[("foo", &coins(1, "ELF"))]);
+        querier.update_balances(&[
+            ("alice", coins(123, "ELF")),
+            ("bob", vec![coin(456, "ELF"), coin(777, "FLY")]),
+            ("carl", vec![]),
+        ]);
+        // update_balance still returns the old value
+        assert_eq!(
+            querier.update_balance("carl", coins(789, "FLY")),
+            Some(vec![])
+        );
+
+        let wrapper = QuerierWrapper::<Empty>::new(&querier);
+        assert_eq!(
+            wrapper.query_all_balances("alice").unwrap(),
+            coins(123, "ELF")
+        );
+        assert_eq!(
+            wrapper.query_all_balances("bob").unwrap(),
+            vec![coin(456, "ELF"), coin(777, "FLY")]
+        );
+        assert_eq!(
+            wrapper.query_balance("carl", "FLY").unwrap(),
+            coin(789, "FLY")
+        );
+        // other balances are kept
+        assert_eq!(wrapper.query_all_balances("foo").unwrap(), coins(1, "ELF"));
+
+        querier.clear_balances();
+        let wrapper = QuerierWrapper::<Empty>::new(&querier);
+        for addr in ["foo", "alice", "bob", "carl"] {
+            assert_eq!(wrapper.query_all_balances(addr).unwrap(), vec![]);
+        }
+    }
+
     #[test]
     fn bank_querier_all_balances() {
         let addr = String::from("foobar");
@@ -1424,6 +1716,60 @@ mod tests {
         assert_eq!(dels, Some(del2c));
     }
 
+    #[cfg(all(feature = "staking", feature = "cosmwasm_1_3"))]
+    #[test]
+    fn staking_querier_all_delegations_paginated() {
+        let delegator = Addr::unchecked("investor");
+        let delegation = |validator: &str, amount: u128| FullDelegation {
+            delegator: delegator.clone(),
+            validator: validator.to_string(),
+            amount: coin(amount, "ustake"),
+            can_redelegate: coin(amount, "ustake"),
+            accumulated_rewards: coins(0, "ustake"),
+        };
+        let del_a = delegation("validator-a", 100);
+        let del_b = delegation("validator-b", 200);
+        let del_c = delegation("validator-c", 300);
+        let del_d = delegation("validator-d", 400);
+
+        // insertion order differs from validator order
+        let staking = StakingQuerier::new(
+            "ustake",
+            &[],
+            &[del_c.clone(), del_a.clone(), del_d.clone(), del_b.clone()],
+        );
+        let query = |limit: Option<u32>, start_after: Option<&str>| -> Vec<Delegation> {
+            let raw = staking
+                .query(&StakingQuery::AllDelegationsPaginated {
+                    delegator: delegator.to_string(),
+                    limit,
+                    start_after: start_after.map(String::from),
+                })
+                .unwrap()
+                .unwrap();
+            let res: AllDelegationsResponse = from_binary(&raw).unwrap();
+            res.delegations
+        };
+
+        // no pagination returns everything ordered by validator
+        let all: Vec<Delegation> = vec![del_a.into(), del_b.into(), del_c.into(), del_d.into()];
+        assert_eq!(query(None, None), all);
+
+        // limit
+        assert_eq!(query(Some(2), None), all[..2]);
+        assert_eq!(query(Some(10), None), all);
+        assert_eq!(query(Some(0), None), vec![]);
+
+        // start_after
+        assert_eq!(query(None, Some("validator-b")), all[2..]);
+        assert_eq!(query(None, Some("validator-bb")), all[2..]);
+        assert_eq!(query(None, Some("validator-d")), vec![]);
+
+        // both
+        assert_eq!(query(Some(1), Some("validator-a")), all[1..2]);
+        assert_eq!(query(Some(2), Some("validator-b")), all[2..]);
+    }
+
     #[test]
     fn wasm_querier_works() {
         let mut querier = WasmQuerier::default();
@@ -1576,6 +1922,67 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wasm_querier_contract_state_works() {
+        let mut querier = WasmQuerier::default();
+        querier.update_contract_state(
+            "contract1",
+            &[(b"config", br#"{"owner":"alice"}"#), (b"count", b"42")],
+        );
+
+        let raw = |contract_addr: &str, key: &[u8]| {
+            querier.query(&WasmQuery::Raw {
+                contract_addr: contract_addr.to_string(),
+                key: key.into(),
+            })
+        };
+
+        // registered keys
+        let value = raw("contract1", b"config").unwrap().unwrap();
+        assert_eq!(value, Binary::from(br#"{"owner":"alice"}"#));
+        let value = raw("contract1", b"count").unwrap().unwrap();
+        assert_eq!(value, Binary::from(b"42"));
+
+        // missing key returns empty value
+        let value = raw("contract1", b"missing").unwrap().unwrap();
+        assert_eq!(value, Binary::default());
+
+        // unregistered contracts and other queries are passed to the handler
+        match raw("contract2", b"count").unwrap_err() {
+            SystemError::NoSuchContract { addr } => assert_eq!(addr, "contract2"),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        match querier
+            .query(&WasmQuery::Smart {
+                contract_addr: "contract1".to_string(),
+                msg: b"{}".into(),
+            })
+            .unwrap_err()
+        {
+            SystemError::NoSuchContract { addr } => assert_eq!(addr, "contract1"),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // state can be replaced
+        querier.update_contract_state("contract1", &[(b"count", b"43")]);
+        let value = querier
+            .query(&WasmQuery::Raw {
+                contract_addr: "contract1".to_string(),
+                key: b"count".into(),
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, Binary::from(b"43"));
+        let value = querier
+            .query(&WasmQuery::Raw {
+                contract_addr: "contract1".to_string(),
+                key: b"config".into(),
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, Binary::default());
+    }
+
     #[test]
     fn riffle_shuffle_works() {
         // Example from https://en.wikipedia.org/wiki/In_shuffle
@@ -1608,6 +2015,25 @@ mod tests {
         assert_eq!(result, original);
     }
 
+    #[test]
+    fn try_riffle_shuffle_works() {
+        let start = [0xA, 0x2, 0x3, 0x4, 0x5, 0x6];
+        let round1 = try_riffle_shuffle(&start).unwrap();
+        assert_eq!(round1, riffle_shuffle(&start));
+        assert_eq!(round1, [0x4, 0xA, 0x5, 0x2, 0x6, 0x3]);
+
+        let empty: [u8; 0] = [];
+        assert_eq!(try_riffle_shuffle(&empty).unwrap(), Vec::<u8>::new());
+
+        let odd = [0xA, 0x2, 0x3];
+        let err = try_riffle_shuffle(&odd).unwrap_err();
+        assert_eq!(err, ShuffleError::OddLength { length: 3 });
+        assert_eq!(
+            err.to_string(),
+            "Riffle shuffle is only defined for an even number of elements, got 3"
+        );
+    }
+
     #[test]
     fn digit_sum_works() {
         assert_eq!(digit_sum(&[]), 0);
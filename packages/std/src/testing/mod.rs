@@ -6,14 +6,15 @@
 mod assertions;
 mod mock;
 
-pub use assertions::assert_approx_eq_impl;
+pub use assertions::{assert_approx_eq_impl, assert_attribute, assert_event, assert_no_messages};
 
 #[cfg(feature = "staking")]
 pub use mock::StakingQuerier;
 pub use mock::{
     digit_sum, mock_dependencies, mock_dependencies_with_balance, mock_dependencies_with_balances,
-    mock_env, mock_info, mock_wasmd_attr, riffle_shuffle, BankQuerier, MockApi, MockQuerier,
-    MockQuerierCustomHandlerResult, MockStorage, MOCK_CONTRACT_ADDR,
+    mock_env, mock_env_at, mock_info, mock_wasmd_attr, riffle_shuffle, try_riffle_shuffle,
+    BankQuerier, MockApi, MockQuerier, MockQuerierCustomHandlerResult, MockStorage, ShuffleError,
+    FIXED_BLOCK_HEIGHT, FIXED_BLOCK_TIME, FIXED_CHAIN_ID, MOCK_CONTRACT_ADDR,
 };
 #[cfg(feature = "stargate")]
 pub use mock::{
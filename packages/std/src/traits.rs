@@ -6,7 +6,7 @@ use crate::addresses::{Addr, CanonicalAddr};
 use crate::binary::Binary;
 use crate::coin::Coin;
 use crate::errors::{
-    HashCalculationError, RecoverPubkeyError, StdError, StdResult, VerificationError,
+    HashCalculationError, RecoverPubkeyError, StdError, StdResult, SystemError, VerificationError,
 };
 #[cfg(feature = "iterator")]
 use crate::iterator::{Order, Record};
@@ -20,6 +20,8 @@ use crate::query::{
     AllDelegationsResponse, AllValidatorsResponse, BondedDenomResponse, Delegation,
     DelegationResponse, FullDelegation, StakingQuery, Validator, ValidatorResponse,
 };
+#[cfg(feature = "cosmwasm_1_3")]
+use crate::query::{DenomMetadata, DenomMetadataResponse};
 use crate::results::{ContractResult, Empty, SystemResult};
 use crate::serde::{from_binary, to_binary, to_vec};
 use crate::ContractInfoResponse;
@@ -34,6 +36,22 @@ pub trait Storage {
     /// is not great yet and might not be possible in all backends. But we're trying to get there.
     fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
 
+    /// Returns `length` bytes of the value at `key` starting at `offset`.
+    /// The result is shorter or empty if the value ends before `offset + length`.
+    /// Returns None when key does not exist.
+    ///
+    /// In contracts only the requested slice is copied into the contract's memory. Depending on
+    /// the chain, the gas charged may still cover the full value. The default implementation
+    /// reads the whole value via [`Storage::get`].
+    fn get_range(&self, key: &[u8], offset: usize, length: usize) -> Option<Vec<u8>> {
+        self.get(key).map(|mut data| {
+            let start = offset.min(data.len());
+            let end = start.saturating_add(length).min(data.len());
+            data.truncate(end);
+            data.split_off(start)
+        })
+    }
+
     #[cfg(feature = "iterator")]
     /// Allows iteration over a set of key/value pairs, either forwards or backwards.
     ///
@@ -218,6 +236,16 @@ impl<'a, C: CustomQuery> QuerierWrapper<'a, C> {
         Ok(res.amount)
     }
 
+    #[cfg(feature = "cosmwasm_1_3")]
+    pub fn query_denom_metadata(&self, denom: impl Into<String>) -> StdResult<DenomMetadata> {
+        let request = BankQuery::DenomMetadata {
+            denom: denom.into(),
+        }
+        .into();
+        let res: DenomMetadataResponse = self.query(&request)?;
+        Ok(res.metadata)
+    }
+
     pub fn query_balance(
         &self,
         address: impl Into<String>,
@@ -308,6 +336,30 @@ impl<'a, C: CustomQuery> QuerierWrapper<'a, C> {
         self.query(&request)
     }
 
+    /// Returns true if a contract is instantiated at the given address.
+    ///
+    /// This is a contract info query that maps the `NoSuchContract` system error to `false`.
+    pub fn query_wasm_contract_exists(&self, contract_addr: impl Into<String>) -> StdResult<bool> {
+        let request: QueryRequest<Empty> = WasmQuery::ContractInfo {
+            contract_addr: contract_addr.into(),
+        }
+        .into();
+        let raw = to_vec(&request).map_err(|serialize_err| {
+            StdError::generic_err(format!("Serializing QueryRequest: {}", serialize_err))
+        })?;
+        match self.raw_query(&raw) {
+            SystemResult::Err(SystemError::NoSuchContract { .. }) => Ok(false),
+            SystemResult::Err(system_err) => Err(StdError::generic_err(format!(
+                "Querier system error: {}",
+                system_err
+            ))),
+            SystemResult::Ok(ContractResult::Err(contract_err)) => Err(StdError::generic_err(
+                format!("Querier contract error: {}", contract_err),
+            )),
+            SystemResult::Ok(ContractResult::Ok(_)) => Ok(true),
+        }
+    }
+
     #[cfg(feature = "staking")]
     pub fn query_all_validators(&self) -> StdResult<Vec<Validator>> {
         let request = StakingQuery::AllValidators {}.into();
@@ -345,6 +397,25 @@ impl<'a, C: CustomQuery> QuerierWrapper<'a, C> {
         Ok(res.delegations)
     }
 
+    /// Returns at most `limit` delegations of the delegator to validators with an address
+    /// greater than `start_after`, ordered by validator address.
+    #[cfg(all(feature = "staking", feature = "cosmwasm_1_3"))]
+    pub fn query_all_delegations_paginated(
+        &self,
+        delegator: impl Into<String>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Delegation>> {
+        let request = StakingQuery::AllDelegationsPaginated {
+            delegator: delegator.into(),
+            limit,
+            start_after,
+        }
+        .into();
+        let res: AllDelegationsResponse = self.query(&request)?;
+        Ok(res.delegations)
+    }
+
     #[cfg(feature = "staking")]
     pub fn query_delegation(
         &self,
@@ -446,7 +517,7 @@ mod tests {
             }) {
                 SystemResult::Ok(ContractResult::Ok(to_binary(&mock_resp()).unwrap()))
             } else {
-                SystemResult::Err(crate::SystemError::NoSuchContract {
+                SystemResult::Err(SystemError::NoSuchContract {
                     addr: ACCT.to_string(),
                 })
             }
@@ -457,6 +528,31 @@ mod tests {
         assert_eq!(contract_info, mock_resp());
     }
 
+    #[test]
+    fn contract_exists() {
+        let mut querier: MockQuerier<Empty> = MockQuerier::new(&[]);
+        querier.update_wasm(|q| -> QuerierResult {
+            match q {
+                WasmQuery::ContractInfo { contract_addr } if contract_addr == "foobar" => {
+                    let response = ContractInfoResponse::new(4, "creator");
+                    SystemResult::Ok(ContractResult::Ok(to_binary(&response).unwrap()))
+                }
+                WasmQuery::ContractInfo { contract_addr } if contract_addr == "broken" => {
+                    SystemResult::Err(SystemError::Unknown {})
+                }
+                _ => SystemResult::Err(SystemError::NoSuchContract {
+                    addr: "bazbar".to_string(),
+                }),
+            }
+        });
+        let wrapper = QuerierWrapper::<Empty>::new(&querier);
+
+        assert!(wrapper.query_wasm_contract_exists("foobar").unwrap());
+        assert!(!wrapper.query_wasm_contract_exists("bazbar").unwrap());
+        // other errors are not swallowed
+        wrapper.query_wasm_contract_exists("broken").unwrap_err();
+    }
+
     #[test]
     fn contract_info_err() {
         const ACCT: &str = "foobar";
@@ -477,7 +573,7 @@ mod tests {
             }) {
                 SystemResult::Ok(ContractResult::Ok(to_binary(&mock_resp()).unwrap()))
             } else {
-                SystemResult::Err(crate::SystemError::NoSuchContract {
+                SystemResult::Err(SystemError::NoSuchContract {
                     addr: ACCT.to_string(),
                 })
             }
@@ -1,8 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crate::addresses::Addr;
 use crate::coin::Coin;
+use crate::math::Uint128;
 use crate::timestamp::Timestamp;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -105,7 +107,127 @@ pub struct MessageInfo {
     pub funds: Vec<Coin>,
 }
 
+impl MessageInfo {
+    /// Creates a `MessageInfo` for the given sender without any funds.
+    ///
+    /// Please note that the VM does not validate `MessageInfo` in any way. The funds
+    /// are passed to the contract as given, which allows hosts to use synthetic values
+    /// e.g. for simulations.
+    pub fn empty(sender: Addr) -> Self {
+        MessageInfo {
+            sender,
+            funds: vec![],
+        }
+    }
+
+    /// Creates a `MessageInfo` for the given sender and funds.
+    ///
+    /// Please note that the VM does not validate `MessageInfo` in any way. The funds
+    /// are passed to the contract as given, i.e. no check is performed whether the sender
+    /// owns them.
+    pub fn with_funds(sender: Addr, funds: Vec<Coin>) -> Self {
+        MessageInfo { sender, funds }
+    }
+
+    /// Returns a copy in which the funds are sorted by denom and coins of the same denom
+    /// are merged into one. `self` is left untouched.
+    ///
+    /// Neither the host nor the VM guarantee an order of `funds` or that each denom occurs
+    /// only once, so use this before comparing funds against expected values.
+    ///
+    /// Panics if the sum of the amounts of a denom overflows.
+    pub fn normalized(&self) -> Self {
+        let mut amounts = BTreeMap::<&str, Uint128>::new();
+        for coin in &self.funds {
+            *amounts.entry(coin.denom.as_str()).or_default() += coin.amount;
+        }
+        MessageInfo {
+            sender: self.sender.clone(),
+            funds: amounts
+                .into_iter()
+                .map(|(denom, amount)| Coin::new(amount.u128(), denom))
+                .collect(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ContractInfo {
     pub address: Addr,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coins;
+
+    #[test]
+    fn message_info_empty_works() {
+        let info = MessageInfo::empty(Addr::unchecked("creator"));
+        assert_eq!(
+            info,
+            MessageInfo {
+                sender: Addr::unchecked("creator"),
+                funds: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn message_info_with_funds_works() {
+        let info = MessageInfo::with_funds(Addr::unchecked("creator"), coins(123, "ucosm"));
+        assert_eq!(
+            info,
+            MessageInfo {
+                sender: Addr::unchecked("creator"),
+                funds: coins(123, "ucosm"),
+            }
+        );
+
+        let info = MessageInfo::with_funds(Addr::unchecked("creator"), vec![]);
+        assert_eq!(info, MessageInfo::empty(Addr::unchecked("creator")));
+    }
+
+    #[test]
+    fn message_info_normalized_works() {
+        let info = MessageInfo::with_funds(
+            Addr::unchecked("creator"),
+            vec![
+                Coin::new(5, "utoken"),
+                Coin::new(100, "uatom"),
+                Coin::new(7, "utoken"),
+                Coin::new(0, "ucosm"),
+                Coin::new(1, "uatom"),
+            ],
+        );
+        let normalized = info.normalized();
+        assert_eq!(normalized.sender, info.sender);
+        assert_eq!(
+            normalized.funds,
+            vec![
+                Coin::new(101, "uatom"),
+                Coin::new(0, "ucosm"),
+                Coin::new(12, "utoken"),
+            ]
+        );
+        // raw field is unchanged
+        assert_eq!(info.funds.len(), 5);
+        assert_eq!(info.funds[0], Coin::new(5, "utoken"));
+
+        // normalizing is idempotent
+        assert_eq!(normalized.normalized(), normalized);
+
+        let info = MessageInfo::empty(Addr::unchecked("creator"));
+        assert_eq!(info.normalized(), info);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to add with overflow")]
+    fn message_info_normalized_panics_on_overflow() {
+        let info = MessageInfo::with_funds(
+            Addr::unchecked("creator"),
+            vec![Coin::new(u128::MAX, "uatom"), Coin::new(1, "uatom")],
+        );
+        info.normalized();
+    }
+}
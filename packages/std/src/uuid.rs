@@ -5,7 +5,7 @@ use std::str::FromStr;
 use uuid as raw_uuid;
 
 use crate::{from_slice, to_vec};
-use crate::{Api, Env, StdResult, Storage};
+use crate::{Api, Env, StdResult, Storage, UuidError};
 
 /// Uuid Provides a Uuid that can be used deterministically.
 /// Use internally Uuidv5 and NAMESPACE_OID.
@@ -64,12 +64,12 @@ impl Deref for Uuid {
 }
 
 impl FromStr for Uuid {
-    type Err = uuid::Error;
+    type Err = UuidError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let parsed = raw_uuid::Uuid::parse_str(s);
         match parsed {
             Ok(data) => Ok(Uuid(data)),
-            Err(err) => Err(err),
+            Err(err) => Err(UuidError::new(err)),
         }
     }
 }
@@ -78,7 +78,7 @@ impl FromStr for Uuid {
 mod tests {
     use crate::testing::{mock_env, MockApi, MockStorage};
     use crate::{new_uuid, Uuid};
-    use crate::{to_vec, Addr, Storage};
+    use crate::{to_vec, Addr, StdError, Storage};
     use std::str::FromStr;
     use uuid as raw_uuid;
 
@@ -131,4 +131,25 @@ mod tests {
         let uuid = new_uuid(&env, &mut storage, &api);
         assert!(uuid.is_ok());
     }
+
+    #[test]
+    fn from_str_fails_for_malformed_uuid() {
+        let err = Uuid::from_str("849c1f99-e882-53e6-8e63").unwrap_err();
+        assert_eq!(err.msg, "invalid group count: expected 5, found 4");
+        assert_eq!(
+            err.to_string(),
+            "Invalid UUID: invalid group count: expected 5, found 4"
+        );
+
+        let err = Uuid::from_str("849c1f99-e882-53e6-8e63-e5aa001359cx").unwrap_err();
+        match StdError::from(err) {
+            StdError::ParseErr {
+                target_type, msg, ..
+            } => {
+                assert_eq!(target_type, "Uuid");
+                assert!(msg.starts_with("invalid character"), "{}", msg);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
 }
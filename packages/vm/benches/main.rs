@@ -12,7 +12,7 @@ use cosmwasm_vm::testing::{
 };
 use cosmwasm_vm::{
     call_execute, call_instantiate, capabilities_from_csv, Cache, CacheOptions, Checksum, Instance,
-    InstanceOptions, Size,
+    InstanceOptions, Size, DEFAULT_MAX_DATA_SEGMENTS_SIZE,
 };
 
 // Instance
@@ -21,6 +21,7 @@ const DEFAULT_GAS_LIMIT: u64 = 1_000_000_000_000; // ~1ms
 const DEFAULT_INSTANCE_OPTIONS: InstanceOptions = InstanceOptions {
     gas_limit: DEFAULT_GAS_LIMIT,
     print_debug: false,
+    ..InstanceOptions::DEFAULT
 };
 const HIGH_GAS_LIMIT: u64 = 20_000_000_000_000_000; // ~20s, allows many calls on one instance
 
@@ -104,13 +105,12 @@ fn bench_instance(c: &mut Criterion) {
 
         let mut gas_used = 0;
         b.iter(|| {
-            let gas_before = instance.get_gas_left();
             let info = mock_info("hasher", &[]);
             let msg = br#"{"argon2":{"mem_cost":256,"time_cost":3}}"#;
             let contract_result =
                 call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg).unwrap();
             assert!(contract_result.into_result().is_ok());
-            gas_used = gas_before - instance.get_gas_left();
+            gas_used = instance.create_gas_report().limit - instance.get_gas_left();
         });
         println!("Gas used: {}", gas_used);
     });
@@ -126,6 +126,9 @@ fn bench_cache(c: &mut Criterion) {
         available_capabilities: capabilities_from_csv("iterator,staking"),
         memory_cache_size: MEMORY_CACHE_SIZE,
         instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+        compile_timeout: None,
+        accept_legacy_interface_versions: false,
+        max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
     };
 
     group.bench_function("save wasm", |b| {
@@ -166,6 +169,9 @@ fn bench_cache(c: &mut Criterion) {
             available_capabilities: capabilities_from_csv("iterator,staking"),
             memory_cache_size: Size(0),
             instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+            compile_timeout: None,
+            accept_legacy_interface_versions: false,
+            max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
         };
         let cache: Cache<MockApi, MockStorage, MockQuerier> =
             unsafe { Cache::new(non_memcache).unwrap() };
@@ -232,6 +238,9 @@ pub fn bench_instance_threads(c: &mut Criterion) {
             available_capabilities: capabilities_from_csv("iterator,staking"),
             memory_cache_size: MEMORY_CACHE_SIZE,
             instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+            compile_timeout: None,
+            accept_legacy_interface_versions: false,
+            max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
         };
 
         let cache: Cache<MockApi, MockStorage, MockQuerier> =
@@ -6,7 +6,7 @@ use cosmwasm_std::{coins, Empty};
 use cosmwasm_vm::testing::{mock_backend, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
 use cosmwasm_vm::{
     call_execute, call_instantiate, capabilities_from_csv, Cache, CacheOptions, InstanceOptions,
-    Size,
+    Size, DEFAULT_MAX_DATA_SEGMENTS_SIZE,
 };
 
 // Instance
@@ -15,6 +15,7 @@ const DEFAULT_GAS_LIMIT: u64 = 400_000 * 150_000;
 const DEFAULT_INSTANCE_OPTIONS: InstanceOptions = InstanceOptions {
     gas_limit: DEFAULT_GAS_LIMIT,
     print_debug: false,
+    ..InstanceOptions::DEFAULT
 };
 // Cache
 const MEMORY_CACHE_SIZE: Size = Size::mebi(200);
@@ -31,6 +32,9 @@ pub fn main() {
         available_capabilities: capabilities_from_csv("iterator,staking"),
         memory_cache_size: MEMORY_CACHE_SIZE,
         instance_memory_limit: DEFAULT_MEMORY_LIMIT,
+        compile_timeout: None,
+        accept_legacy_interface_versions: false,
+        max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
     };
 
     let cache: Cache<MockApi, MockStorage, MockQuerier> = unsafe { Cache::new(options).unwrap() };
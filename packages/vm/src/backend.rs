@@ -84,6 +84,31 @@ pub trait Storage {
     /// is not great yet and might not be possible in all backends. But we're trying to get there.
     fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>>;
 
+    /// Returns `length` bytes of the value at `key` starting at `offset`.
+    /// The result is shorter or empty if the value ends before `offset + length`.
+    /// Returns Ok(None) when key does not exist.
+    ///
+    /// The default implementation reads the whole value via [`Storage::get`] and reports the gas
+    /// of that full read. Backends that can read parts of a value can override this to charge
+    /// less gas.
+    fn get_range(
+        &self,
+        key: &[u8],
+        offset: usize,
+        length: usize,
+    ) -> BackendResult<Option<Vec<u8>>> {
+        let (result, gas_info) = self.get(key);
+        let result = result.map(|value| {
+            value.map(|mut data| {
+                let start = offset.min(data.len());
+                let end = start.saturating_add(length).min(data.len());
+                data.truncate(end);
+                data.split_off(start)
+            })
+        });
+        (result, gas_info)
+    }
+
     /// Allows iteration over a set of key/value pairs, either forwards or backwards.
     /// Returns an interator ID that is unique within the Storage instance.
     ///
@@ -133,6 +158,34 @@ pub trait Storage {
 pub trait BackendApi: Copy + Clone + Send {
     fn canonical_address(&self, human: &str) -> BackendResult<Vec<u8>>;
     fn human_address(&self, canonical: &[u8]) -> BackendResult<String>;
+
+    /// Checks that the given human readable address is valid and normalized.
+    /// The returned gas info covers the whole validation.
+    ///
+    /// The default implementation converts the address to its canonical form and back.
+    /// Backends with a cheaper way to validate addresses should override this.
+    fn addr_validate(&self, human: &str) -> BackendResult<()> {
+        let (result, mut gas_info) = self.canonical_address(human);
+        let canonical = match result {
+            Ok(canonical) => canonical,
+            Err(err) => return (Err(err), gas_info),
+        };
+
+        let (result, humanize_gas_info) = self.human_address(&canonical);
+        gas_info += humanize_gas_info;
+        let normalized = match result {
+            Ok(normalized) => normalized,
+            Err(err) => return (Err(err), gas_info),
+        };
+
+        if normalized != human {
+            return (
+                Err(BackendError::user_err("Address is not normalized")),
+                gas_info,
+            );
+        }
+        (Ok(()), gas_info)
+    }
 }
 
 pub trait Querier {
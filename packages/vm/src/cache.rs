@@ -4,17 +4,18 @@ use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use crate::backend::{Backend, BackendApi, Querier, Storage};
 use crate::capabilities::required_capabilities_from_module;
 use crate::checksum::Checksum;
-use crate::compatibility::check_wasm;
+use crate::compatibility::check_wasm_for_cache;
 use crate::errors::{VmError, VmResult};
 use crate::instance::{Instance, InstanceOptions};
 use crate::modules::{FileSystemCache, InMemoryCache, PinnedMemoryCache};
 use crate::size::Size;
 use crate::static_analysis::{deserialize_wasm, has_ibc_entry_points};
-use crate::wasm_backend::{compile, make_runtime_store};
+use crate::wasm_backend::{compile, compile_with_timeout, make_runtime_store};
 
 const STATE_DIR: &str = "state";
 // Things related to the state of the blockchain.
@@ -49,6 +50,23 @@ pub struct CacheOptions {
     /// Memory limit for instances, in bytes. Use a value that is divisible by the Wasm page size 65536,
     /// e.g. full MiBs.
     pub instance_memory_limit: Size,
+    /// Max time to compile a contract in [`Cache::save_wasm`]. `None` means no limit.
+    ///
+    /// This protects the node against contracts that are very slow to compile. Since the
+    /// duration depends on the machine, only use it where a node-local decision is fine,
+    /// e.g. for checking uploads before they are included in a block.
+    ///
+    /// A compilation that times out cannot be interrupted and keeps running on a background
+    /// thread until it finishes. At most 16 such compilations run at the same time in the
+    /// process. Beyond that, [`Cache::save_wasm`] fails with a compile error.
+    pub compile_timeout: Option<Duration>,
+    /// Accept contracts with legacy interface versions in [`Cache::save_wasm`], e.g. during
+    /// a migration window. This only has an effect for versions enabled at compile time
+    /// via the `allow_interface_version_7` feature.
+    pub accept_legacy_interface_versions: bool,
+    /// Max summed up length of all data segments of a contract in [`Cache::save_wasm`], in bytes.
+    /// Use [`crate::DEFAULT_MAX_DATA_SEGMENTS_SIZE`] for a generous limit.
+    pub max_data_segments_size: usize,
 }
 
 pub struct CacheInner {
@@ -66,6 +84,9 @@ pub struct Cache<A: BackendApi, S: Storage, Q: Querier> {
     /// Available capabilities are immutable for the lifetime of the cache,
     /// i.e. any number of read-only references is allowed to access it concurrently.
     available_capabilities: HashSet<String>,
+    compile_timeout: Option<Duration>,
+    accept_legacy_interface_versions: bool,
+    max_data_segments_size: usize,
     inner: Mutex<CacheInner>,
     // Those two don't store data but only fix type information
     type_api: PhantomData<A>,
@@ -81,6 +102,18 @@ pub struct AnalysisReport {
     pub required_capabilities: HashSet<String>,
 }
 
+/// Compares the capabilities required by a contract with the ones available in the cache
+#[derive(PartialEq, Eq, Debug)]
+pub struct CapabilityDiff {
+    /// Capabilities required by the contract
+    pub required: HashSet<String>,
+    /// Capabilities available in the cache
+    pub available: HashSet<String>,
+    /// Required capabilities that are not available. If this is not empty, the contract
+    /// cannot be used with this cache.
+    pub missing: HashSet<String>,
+}
+
 impl<A, S, Q> Cache<A, S, Q>
 where
     A: BackendApi + 'static, // 'static is needed by `impl<…> Instance`
@@ -100,6 +133,9 @@ where
             available_capabilities,
             memory_cache_size,
             instance_memory_limit,
+            compile_timeout,
+            accept_legacy_interface_versions,
+            max_data_segments_size,
         } = options;
 
         let state_path = base_dir.join(STATE_DIR);
@@ -122,6 +158,9 @@ where
             .map_err(|e| VmError::cache_err(format!("Error file system cache: {}", e)))?;
         Ok(Cache {
             available_capabilities,
+            compile_timeout,
+            accept_legacy_interface_versions,
+            max_data_segments_size,
             inner: Mutex::new(CacheInner {
                 wasm_path,
                 instance_memory_limit,
@@ -141,6 +180,12 @@ where
         self.inner.lock().unwrap().stats
     }
 
+    /// Resets the hit and miss counters to zero and returns the stats from before the reset.
+    /// The cached modules are not affected.
+    pub fn reset_stats(&self) -> Stats {
+        std::mem::take(&mut self.inner.lock().unwrap().stats)
+    }
+
     pub fn metrics(&self) -> Metrics {
         let cache = self.inner.lock().unwrap();
         Metrics {
@@ -153,8 +198,13 @@ where
     }
 
     pub fn save_wasm(&self, wasm: &[u8]) -> VmResult<Checksum> {
-        check_wasm(wasm, &self.available_capabilities)?;
-        let module = compile(wasm, None, &[])?;
+        check_wasm_for_cache(
+            wasm,
+            &self.available_capabilities,
+            self.accept_legacy_interface_versions,
+            self.max_data_segments_size,
+        )?;
+        let module = compile_with_timeout(wasm, None, &[], self.compile_timeout)?;
 
         let mut cache = self.inner.lock().unwrap();
         let checksum = save_wasm_to_disk(&cache.wasm_path, wasm)?;
@@ -162,6 +212,14 @@ where
         Ok(checksum)
     }
 
+    /// Validates and stores each of the given Wasm blobs like [`Cache::save_wasm`].
+    ///
+    /// The results are returned in the order of the input. A failure does not abort
+    /// the batch, i.e. the remaining contracts are still stored.
+    pub fn save_wasm_batch(&self, contracts: &[&[u8]]) -> Vec<VmResult<Checksum>> {
+        contracts.iter().map(|wasm| self.save_wasm(wasm)).collect()
+    }
+
     /// Retrieves a Wasm blob that was previously stored via save_wasm.
     /// When the cache is instantiated with the same base dir, this finds Wasm files on disc across multiple cache instances (i.e. node restarts).
     /// This function is public to allow a checksum to Wasm lookup in the blockchain.
@@ -195,6 +253,25 @@ where
         })
     }
 
+    /// Compares the capabilities required by this Wasm with the available capabilities
+    /// of the cache without compiling or instantiating it.
+    ///
+    /// Like [`Cache::analyze`], this can be called at any point in time once the contract was stored.
+    pub fn capability_diff(&self, checksum: &Checksum) -> VmResult<CapabilityDiff> {
+        let wasm = self.load_wasm(checksum)?;
+        let module = deserialize_wasm(&wasm)?;
+        let required = required_capabilities_from_module(&module);
+        let missing = required
+            .difference(&self.available_capabilities)
+            .cloned()
+            .collect();
+        Ok(CapabilityDiff {
+            required,
+            available: self.available_capabilities.clone(),
+            missing,
+        })
+    }
+
     /// Pins a Module that was previously stored via save_wasm.
     ///
     /// The module is lookup first in the memory cache, and then in the file system cache.
@@ -248,6 +325,18 @@ where
             .remove(checksum)
     }
 
+    /// Loads the modules of previously saved Wasms into the memory cache,
+    /// e.g. to warm up the cache at node startup. Pinned modules are left untouched.
+    ///
+    /// Returns one result per checksum, such that a missing or broken contract
+    /// does not prevent the others from being loaded.
+    pub fn preload(&self, checksums: &[Checksum]) -> Vec<VmResult<()>> {
+        checksums
+            .iter()
+            .map(|checksum| self.get_module(checksum).map(|_| ()))
+            .collect()
+    }
+
     /// Returns an Instance tied to a previously saved Wasm.
     ///
     /// It takes a module from cache or Wasm code and instantiates it.
@@ -261,8 +350,7 @@ where
         let instance = Instance::from_module(
             &module,
             backend,
-            options.gas_limit,
-            options.print_debug,
+            options,
             None,
             Some(&self.instantiation_lock),
         )?;
@@ -370,6 +458,7 @@ mod tests {
     use super::*;
     use crate::calls::{call_execute, call_instantiate};
     use crate::capabilities::capabilities_from_csv;
+    use crate::compatibility::DEFAULT_MAX_DATA_SEGMENTS_SIZE;
     use crate::errors::VmError;
     use crate::testing::{mock_backend, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
     use cosmwasm_std::{coins, Empty};
@@ -382,6 +471,7 @@ mod tests {
     const TESTING_OPTIONS: InstanceOptions = InstanceOptions {
         gas_limit: TESTING_GAS_LIMIT,
         print_debug: false,
+        ..InstanceOptions::DEFAULT
     };
     const TESTING_MEMORY_CACHE_SIZE: Size = Size::mebi(200);
 
@@ -398,6 +488,9 @@ mod tests {
             available_capabilities: default_capabilities(),
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            compile_timeout: None,
+            accept_legacy_interface_versions: false,
+            max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
         }
     }
 
@@ -409,6 +502,9 @@ mod tests {
             available_capabilities: capabilities,
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            compile_timeout: None,
+            accept_legacy_interface_versions: false,
+            max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
         }
     }
 
@@ -492,6 +588,9 @@ mod tests {
                 available_capabilities: default_capabilities(),
                 memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
                 instance_memory_limit: TESTING_MEMORY_LIMIT,
+                compile_timeout: None,
+                accept_legacy_interface_versions: false,
+                max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
             };
             let cache1: Cache<MockApi, MockStorage, MockQuerier> =
                 unsafe { Cache::new(options1).unwrap() };
@@ -504,6 +603,9 @@ mod tests {
                 available_capabilities: default_capabilities(),
                 memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
                 instance_memory_limit: TESTING_MEMORY_LIMIT,
+                compile_timeout: None,
+                accept_legacy_interface_versions: false,
+                max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
             };
             let cache2: Cache<MockApi, MockStorage, MockQuerier> =
                 unsafe { Cache::new(options2).unwrap() };
@@ -530,6 +632,147 @@ mod tests {
         }
     }
 
+    #[test]
+    fn save_wasm_batch_works() {
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(make_testing_options()).unwrap() };
+
+        let results = cache.save_wasm_batch(&[b"broken wasm", CONTRACT]);
+        assert_eq!(results.len(), 2);
+        match &results[0] {
+            Err(VmError::StaticValidationErr { .. }) => {}
+            res => panic!("Unexpected result: {:?}", res),
+        }
+        let checksum = results[1].as_ref().unwrap();
+        assert_eq!(*checksum, Checksum::generate(CONTRACT));
+        assert_eq!(cache.load_wasm(checksum).unwrap(), CONTRACT);
+
+        assert!(cache.save_wasm_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn save_wasm_respects_compile_timeout() {
+        let options = CacheOptions {
+            compile_timeout: Some(Duration::from_nanos(1)),
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+        match cache.save_wasm(CONTRACT).unwrap_err() {
+            VmError::CompileTimeout { timeout, .. } => {
+                assert_eq!(timeout, Duration::from_nanos(1))
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        let options = CacheOptions {
+            compile_timeout: Some(Duration::from_secs(600)),
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+        cache.load_wasm(&checksum).unwrap();
+    }
+
+    #[test]
+    fn save_wasm_respects_accept_legacy_interface_versions() {
+        let wasm_v7 = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_7" (func 0))
+            (export "allocate" (func 1))
+            (export "deallocate" (func 2))
+            (export "instantiate" (func 0))
+        )"#,
+        )
+        .unwrap();
+
+        // disabled
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(make_testing_options()).unwrap() };
+        match cache.save_wasm(&wasm_v7).unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(
+                    msg.starts_with("Wasm contract has unknown interface_version_* marker export")
+                )
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // enabled, but bounded by the allow_interface_version_7 feature
+        let options = CacheOptions {
+            accept_legacy_interface_versions: true,
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+        let result = cache.save_wasm(&wasm_v7);
+        #[cfg(feature = "allow_interface_version_7")]
+        {
+            let checksum = result.unwrap();
+            cache.load_wasm(&checksum).unwrap();
+        }
+        #[cfg(not(feature = "allow_interface_version_7"))]
+        match result.unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(
+                    msg.starts_with("Wasm contract has unknown interface_version_* marker export")
+                )
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn save_wasm_respects_max_data_segments_size() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_8" (func 0))
+            (export "allocate" (func 1))
+            (export "deallocate" (func 2))
+            (export "instantiate" (func 0))
+            (data (i32.const 1024) "0123456789abcdef")
+        )"#,
+        )
+        .unwrap();
+
+        // at the limit
+        let options = CacheOptions {
+            max_data_segments_size: 16,
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+        cache.save_wasm(&wasm).unwrap();
+
+        // above the limit
+        let options = CacheOptions {
+            max_data_segments_size: 15,
+            ..make_testing_options()
+        };
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+        match cache.save_wasm(&wasm).unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => assert_eq!(
+                msg,
+                "Wasm contract's data segments exceed the limit of 15 bytes (got 16 bytes)"
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn load_wasm_errors_for_corrupted_wasm() {
         let tmp_dir = TempDir::new().unwrap();
@@ -538,6 +781,9 @@ mod tests {
             available_capabilities: default_capabilities(),
             memory_cache_size: TESTING_MEMORY_CACHE_SIZE,
             instance_memory_limit: TESTING_MEMORY_LIMIT,
+            compile_timeout: None,
+            accept_legacy_interface_versions: false,
+            max_data_segments_size: DEFAULT_MAX_DATA_SEGMENTS_SIZE,
         };
         let cache: Cache<MockApi, MockStorage, MockQuerier> =
             unsafe { Cache::new(options).unwrap() };
@@ -637,6 +883,68 @@ mod tests {
         assert_eq!(cache.stats().misses, 0);
     }
 
+    #[test]
+    fn reset_stats_works() {
+        let cache = unsafe { Cache::new(make_testing_options()).unwrap() };
+        let checksum = cache.save_wasm(CONTRACT).unwrap();
+
+        // from file system, then from memory
+        let _instance1 = cache
+            .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+            .unwrap();
+        let _instance2 = cache
+            .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+            .unwrap();
+
+        let stats = cache.reset_stats();
+        assert_eq!(stats.hits_pinned_memory_cache, 0);
+        assert_eq!(stats.hits_memory_cache, 1);
+        assert_eq!(stats.hits_fs_cache, 1);
+        assert_eq!(stats.misses, 0);
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
+        assert_eq!(cache.stats().hits_memory_cache, 0);
+        assert_eq!(cache.stats().hits_fs_cache, 0);
+        assert_eq!(cache.stats().misses, 0);
+
+        // cache contents are kept, so this is a memory cache hit
+        let _instance3 = cache
+            .get_instance(&checksum, mock_backend(&[]), TESTING_OPTIONS)
+            .unwrap();
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
+        assert_eq!(cache.stats().hits_memory_cache, 1);
+        assert_eq!(cache.stats().hits_fs_cache, 0);
+        assert_eq!(cache.stats().misses, 0);
+        assert_eq!(cache.metrics().elements_memory_cache, 1);
+    }
+
+    #[test]
+    fn preload_works() {
+        let cache = unsafe { Cache::new(make_stargate_testing_options()).unwrap() };
+        let checksum1 = cache.save_wasm(CONTRACT).unwrap();
+        let checksum2 = cache.save_wasm(IBC_CONTRACT).unwrap();
+        let non_existent = Checksum::from([5u8; 32]);
+
+        let results = cache.preload(&[checksum1, non_existent, checksum2]);
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+        assert_eq!(cache.metrics().elements_memory_cache, 2);
+        assert_eq!(cache.metrics().elements_pinned_memory_cache, 0);
+
+        cache.reset_stats();
+        let _instance1 = cache
+            .get_instance(&checksum1, mock_backend(&[]), TESTING_OPTIONS)
+            .unwrap();
+        let _instance2 = cache
+            .get_instance(&checksum2, mock_backend(&[]), TESTING_OPTIONS)
+            .unwrap();
+        assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
+        assert_eq!(cache.stats().hits_memory_cache, 2);
+        assert_eq!(cache.stats().hits_fs_cache, 0);
+        assert_eq!(cache.stats().misses, 0);
+    }
+
     #[test]
     fn call_instantiate_on_cached_contract() {
         let cache = unsafe { Cache::new(make_testing_options()).unwrap() };
@@ -896,7 +1204,7 @@ mod tests {
         // Init from module cache
         let options = InstanceOptions {
             gas_limit: 10,
-            print_debug: false,
+            ..Default::default()
         };
         let mut instance1 = cache.get_instance(&checksum, backend1, options).unwrap();
         assert_eq!(cache.stats().hits_fs_cache, 1);
@@ -916,7 +1224,7 @@ mod tests {
         // Init from memory cache
         let options = InstanceOptions {
             gas_limit: TESTING_GAS_LIMIT,
-            print_debug: false,
+            ..Default::default()
         };
         let mut instance2 = cache.get_instance(&checksum, backend2, options).unwrap();
         assert_eq!(cache.stats().hits_pinned_memory_cache, 0);
@@ -1005,6 +1313,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn capability_diff_works() {
+        let options = make_stargate_testing_options();
+        let base_dir = options.base_dir.clone();
+        let cache: Cache<MockApi, MockStorage, MockQuerier> =
+            unsafe { Cache::new(options).unwrap() };
+
+        let checksum1 = cache.save_wasm(CONTRACT).unwrap();
+        let diff1 = cache.capability_diff(&checksum1).unwrap();
+        assert_eq!(
+            diff1,
+            CapabilityDiff {
+                required: HashSet::new(),
+                available: capabilities_from_csv("iterator,staking,stargate"),
+                missing: HashSet::new(),
+            }
+        );
+
+        let checksum2 = cache.save_wasm(IBC_CONTRACT).unwrap();
+        let diff2 = cache.capability_diff(&checksum2).unwrap();
+        assert_eq!(diff2.missing, HashSet::new());
+
+        // a cache on the same directory without staking cannot run the IBC contract
+        let cache: Cache<MockApi, MockStorage, MockQuerier> = unsafe {
+            Cache::new(CacheOptions {
+                base_dir,
+                available_capabilities: capabilities_from_csv("iterator,stargate"),
+                ..make_stargate_testing_options()
+            })
+            .unwrap()
+        };
+        let diff2 = cache.capability_diff(&checksum2).unwrap();
+        assert_eq!(
+            diff2,
+            CapabilityDiff {
+                required: capabilities_from_csv("iterator,staking,stargate"),
+                available: capabilities_from_csv("iterator,stargate"),
+                missing: capabilities_from_csv("staking"),
+            }
+        );
+    }
+
     #[test]
     fn pin_unpin_works() {
         let cache = unsafe { Cache::new(make_testing_options()).unwrap() };
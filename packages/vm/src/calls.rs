@@ -1,7 +1,11 @@
 use serde::de::DeserializeOwned;
+use serde::Deserialize;
 use wasmer::Val;
 
-use cosmwasm_std::{ContractResult, CustomMsg, Env, MessageInfo, QueryResponse, Reply, Response};
+use cosmwasm_std::{
+    Attribute, Binary, ContractResult, CosmosMsg, CustomMsg, Env, Event, MessageInfo,
+    QueryResponse, Reply, Response,
+};
 #[cfg(feature = "stargate")]
 use cosmwasm_std::{
     Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg,
@@ -11,8 +15,8 @@ use cosmwasm_std::{
 
 use crate::backend::{BackendApi, Querier, Storage};
 use crate::conversion::ref_to_u32;
-use crate::errors::{VmError, VmResult};
-use crate::instance::Instance;
+use crate::errors::{CommunicationError, VmError, VmResult};
+use crate::instance::{Instance, MsgFilter};
 use crate::serde::{from_slice, to_vec};
 
 /// The limits in here protect the host from allocating an unreasonable amount of memory
@@ -95,6 +99,67 @@ mod deserialization_limits {
     pub const RESULT_IBC_PACKET_TIMEOUT: usize = 256 * KI;
 }
 
+/// Deserializes the result of an entry point call. Parse errors are turned into
+/// a [`VmError::DeserializeErr`], which contains the entry point name and data length.
+fn from_result_slice<'a, T>(
+    data: &'a [u8],
+    deserialization_limit: usize,
+    entry_point: &str,
+) -> VmResult<T>
+where
+    T: Deserialize<'a>,
+{
+    from_slice(data, deserialization_limit).map_err(|err| match err {
+        VmError::ParseErr {
+            target_type, msg, ..
+        } => VmError::deserialize_err(entry_point, data.len(), target_type, msg),
+        err => err,
+    })
+}
+
+/// Rejects messages longer than `max_length` before they are copied into the contract
+fn check_msg_len(msg: &[u8], max_length: Option<usize>) -> VmResult<()> {
+    match max_length {
+        Some(max_length) if msg.len() > max_length => {
+            Err(CommunicationError::region_length_too_big(msg.len(), max_length).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The reserved attribute key used to tag events with the emitting contract's address
+const CONTRACT_ADDRESS_ATTRIBUTE: &str = "_contract_address";
+
+/// Returns the address to tag the events with if `auto_tag_events` is enabled
+fn contract_address_to_tag<A, S, Q>(instance: &Instance<A, S, Q>, env: &Env) -> Option<String>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+{
+    if instance.auto_tag_events() {
+        Some(env.contract.address.to_string())
+    } else {
+        None
+    }
+}
+
+/// Prepends the `_contract_address` attribute to all events, like the chain does
+fn tag_events(contract_address: Option<String>, events: &mut [Event]) {
+    if let Some(contract_address) = contract_address {
+        for event in events {
+            // Attribute::new cannot be used since it rejects reserved keys
+            event.attributes.insert(
+                0,
+                Attribute {
+                    key: CONTRACT_ADDRESS_ATTRIBUTE.to_string(),
+                    value: contract_address.clone(),
+                },
+            );
+        }
+    }
+}
+
 pub fn call_instantiate<A, S, Q, U>(
     instance: &mut Instance<A, S, Q>,
     env: &Env,
@@ -107,11 +172,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let info = to_vec(info)?;
     let data = call_instantiate_raw(instance, &env, &info, msg)?;
-    let result: ContractResult<Response<U>> =
-        from_slice(&data, deserialization_limits::RESULT_INSTANTIATE)?;
+    let mut result: ContractResult<Response<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_INSTANTIATE,
+        "instantiate",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -127,14 +199,63 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let info = to_vec(info)?;
     let data = call_execute_raw(instance, &env, &info, msg)?;
-    let result: ContractResult<Response<U>> =
-        from_slice(&data, deserialization_limits::RESULT_EXECUTE)?;
+    let mut result: ContractResult<Response<U>> =
+        from_result_slice(&data, deserialization_limits::RESULT_EXECUTE, "execute")?;
+    if let ContractResult::Ok(response) = &mut result {
+        if let Some(max_count) = instance.max_messages_per_response() {
+            check_message_count(max_count, response)?;
+        }
+        if let Some(filter) = instance.msg_filter() {
+            check_messages(filter, response)?;
+        }
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
+fn check_message_count<U: CustomMsg>(max_count: usize, response: &Response<U>) -> VmResult<()> {
+    let count = response.messages.len();
+    if count > max_count {
+        return Err(VmError::message_rejected(format!(
+            "Response contains {} messages, which exceeds the limit of {}",
+            count, max_count
+        )));
+    }
+    Ok(())
+}
+
+/// Runs the filter on all messages of the response and fails on the first rejected one
+fn check_messages<U: CustomMsg>(filter: &MsgFilter, response: &Response<U>) -> VmResult<()> {
+    for sub_msg in &response.messages {
+        let msg: CosmosMsg<Binary> = match &sub_msg.msg {
+            CosmosMsg::Bank(msg) => msg.clone().into(),
+            CosmosMsg::Custom(custom) => CosmosMsg::Custom(Binary(to_vec(custom)?)),
+            #[cfg(feature = "staking")]
+            CosmosMsg::Staking(msg) => msg.clone().into(),
+            #[cfg(feature = "staking")]
+            CosmosMsg::Distribution(msg) => msg.clone().into(),
+            #[cfg(feature = "stargate")]
+            CosmosMsg::Stargate { type_url, value } => CosmosMsg::Stargate {
+                type_url: type_url.clone(),
+                value: value.clone(),
+            },
+            #[cfg(feature = "stargate")]
+            CosmosMsg::Ibc(msg) => msg.clone().into(),
+            CosmosMsg::Wasm(msg) => msg.clone().into(),
+            #[cfg(feature = "stargate")]
+            CosmosMsg::Gov(msg) => msg.clone().into(),
+            // variants enabled in cosmwasm-std by other crates but not by the VM's features
+            msg => from_slice(&to_vec(msg)?, deserialization_limits::RESULT_EXECUTE)?,
+        };
+        filter.check(&msg).map_err(VmError::message_rejected)?;
+    }
+    Ok(())
+}
+
 pub fn call_migrate<A, S, Q, U>(
     instance: &mut Instance<A, S, Q>,
     env: &Env,
@@ -146,10 +267,41 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let data = call_migrate_raw(instance, &env, msg)?;
-    let result: ContractResult<Response<U>> =
-        from_slice(&data, deserialization_limits::RESULT_MIGRATE)?;
+    let mut result: ContractResult<Response<U>> =
+        from_result_slice(&data, deserialization_limits::RESULT_MIGRATE, "migrate")?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
+    Ok(result)
+}
+
+/// Like [`call_migrate`] but also passes the `MessageInfo` of the migration, such that the
+/// contract can authorize the sender. Contracts whose migrate entry point does not take
+/// the info are called without it.
+pub fn call_migrate_with_info<A, S, Q, U>(
+    instance: &mut Instance<A, S, Q>,
+    env: &Env,
+    info: &MessageInfo,
+    msg: &[u8],
+) -> VmResult<ContractResult<Response<U>>>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    U: DeserializeOwned + CustomMsg,
+{
+    let contract_address = contract_address_to_tag(instance, env);
+    let env = to_vec(env)?;
+    let info = to_vec(info)?;
+    let data = call_migrate_with_info_raw(instance, &env, &info, msg)?;
+    let mut result: ContractResult<Response<U>> =
+        from_result_slice(&data, deserialization_limits::RESULT_MIGRATE, "migrate")?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -164,10 +316,14 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let data = call_sudo_raw(instance, &env, msg)?;
-    let result: ContractResult<Response<U>> =
-        from_slice(&data, deserialization_limits::RESULT_SUDO)?;
+    let mut result: ContractResult<Response<U>> =
+        from_result_slice(&data, deserialization_limits::RESULT_SUDO, "sudo")?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -182,11 +338,15 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_reply_raw(instance, &env, &msg)?;
-    let result: ContractResult<Response<U>> =
-        from_slice(&data, deserialization_limits::RESULT_REPLY)?;
+    let mut result: ContractResult<Response<U>> =
+        from_result_slice(&data, deserialization_limits::RESULT_REPLY, "reply")?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -203,7 +363,7 @@ where
     let env = to_vec(env)?;
     let data = call_query_raw(instance, &env, msg)?;
     let result: ContractResult<QueryResponse> =
-        from_slice(&data, deserialization_limits::RESULT_QUERY)?;
+        from_result_slice(&data, deserialization_limits::RESULT_QUERY, "query")?;
     // Ensure query response is valid JSON
     if let ContractResult::Ok(binary_response) = &result {
         serde_json::from_slice::<serde_json::Value>(binary_response.as_slice()).map_err(|e| {
@@ -228,8 +388,11 @@ where
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_channel_open_raw(instance, &env, &msg)?;
-    let result: ContractResult<Option<Ibc3ChannelOpenResponse>> =
-        from_slice(&data, deserialization_limits::RESULT_IBC_CHANNEL_OPEN)?;
+    let result: ContractResult<Option<Ibc3ChannelOpenResponse>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_CHANNEL_OPEN,
+        "ibc_channel_open",
+    )?;
     Ok(result)
 }
 
@@ -245,10 +408,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_channel_connect_raw(instance, &env, &msg)?;
-    let result = from_slice(&data, deserialization_limits::RESULT_IBC_CHANNEL_CONNECT)?;
+    let mut result: ContractResult<IbcBasicResponse<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_CHANNEL_CONNECT,
+        "ibc_channel_connect",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -264,10 +435,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_channel_close_raw(instance, &env, &msg)?;
-    let result = from_slice(&data, deserialization_limits::RESULT_IBC_CHANNEL_CLOSE)?;
+    let mut result: ContractResult<IbcBasicResponse<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_CHANNEL_CLOSE,
+        "ibc_channel_close",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -283,10 +462,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_packet_receive_raw(instance, &env, &msg)?;
-    let result = from_slice(&data, deserialization_limits::RESULT_IBC_PACKET_RECEIVE)?;
+    let mut result: ContractResult<IbcReceiveResponse<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_PACKET_RECEIVE,
+        "ibc_packet_receive",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -302,10 +489,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_packet_ack_raw(instance, &env, &msg)?;
-    let result = from_slice(&data, deserialization_limits::RESULT_IBC_PACKET_ACK)?;
+    let mut result: ContractResult<IbcBasicResponse<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_PACKET_ACK,
+        "ibc_packet_ack",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -321,10 +516,18 @@ where
     Q: Querier + 'static,
     U: DeserializeOwned + CustomMsg,
 {
+    let contract_address = contract_address_to_tag(instance, env);
     let env = to_vec(env)?;
     let msg = to_vec(msg)?;
     let data = call_ibc_packet_timeout_raw(instance, &env, &msg)?;
-    let result = from_slice(&data, deserialization_limits::RESULT_IBC_PACKET_TIMEOUT)?;
+    let mut result: ContractResult<IbcBasicResponse<U>> = from_result_slice(
+        &data,
+        deserialization_limits::RESULT_IBC_PACKET_TIMEOUT,
+        "ibc_packet_timeout",
+    )?;
+    if let ContractResult::Ok(response) = &mut result {
+        tag_events(contract_address, &mut response.events);
+    }
     Ok(result)
 }
 
@@ -341,6 +544,7 @@ where
     S: Storage + 'static,
     Q: Querier + 'static,
 {
+    check_msg_len(msg, instance.max_instantiate_msg_len())?;
     instance.set_storage_readonly(false);
     call_raw(
         instance,
@@ -363,6 +567,7 @@ where
     S: Storage + 'static,
     Q: Querier + 'static,
 {
+    check_msg_len(msg, instance.max_execute_msg_len())?;
     instance.set_storage_readonly(false);
     call_raw(
         instance,
@@ -393,6 +598,27 @@ where
     )
 }
 
+/// Calls Wasm export "migrate" with the info if the contract accepts it and returns raw data
+/// from the contract. The result is length limited to prevent abuse but otherwise unchecked.
+pub fn call_migrate_with_info_raw<A, S, Q>(
+    instance: &mut Instance<A, S, Q>,
+    env: &[u8],
+    info: &[u8],
+    msg: &[u8],
+) -> VmResult<Vec<u8>>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+{
+    instance.set_storage_readonly(false);
+    let args: &[&[u8]] = match instance.function_arity("migrate") {
+        Some(3) => &[env, info, msg],
+        _ => &[env, msg],
+    };
+    call_raw(instance, "migrate", args, read_limits::RESULT_MIGRATE)
+}
+
 /// Calls Wasm export "sudo" and returns raw data from the contract.
 /// The result is length limited to prevent abuse but otherwise unchecked.
 pub fn call_sudo_raw<A, S, Q>(
@@ -574,6 +800,19 @@ where
     S: Storage + 'static,
     Q: Querier + 'static,
 {
+    // Optional entry points like "reply" or "migrate" may be missing
+    if !instance.has_function(name) {
+        return Err(VmError::missing_entry_point(name));
+    }
+    instance.reset_gas();
+    instance.reset_vm_allocated_bytes();
+    instance.reset_last_debug_message();
+    #[cfg(feature = "iterator")]
+    instance.reset_iterators();
+    // All entry points take the env as their first argument
+    if let Some(env) = args.first() {
+        instance.set_serialized_env(env);
+    }
     let mut arg_region_ptrs = Vec::<Val>::with_capacity(args.len());
     for arg in args {
         let region_ptr = instance.allocate(arg.len())?;
@@ -591,11 +830,26 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::testing::{mock_env, mock_info, mock_instance};
-    use cosmwasm_std::{coins, Empty};
+    use crate::instance::InstanceOptions;
+    use crate::testing::{
+        assert_storage_readonly, assert_storage_writable, bump_allocator_contract, mock_env,
+        mock_info, mock_instance, mock_instance_with_options, MockInstanceOptions,
+    };
+    use cosmwasm_std::{coins, Empty, Reply, SubMsgResult};
+    use schemars::JsonSchema;
+    use serde::Serialize;
 
     static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
 
+    /// Body of a contract whose `execute` returns a Region pointing to non-JSON data
+    static GARBAGE_RESULT_CONTRACT: &str = r#"
+        (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 256))
+        (func (export "execute") (param i32 i32 i32) (result i32) (i32.const 256))
+        ;; Region { offset: 512, capacity: 5, length: 5 }
+        (data (i32.const 256) "\00\02\00\00\05\00\00\00\05\00\00\00")
+        (data (i32.const 512) "\ff{foo")
+    "#;
+
     #[test]
     fn call_instantiate_works() {
         let mut instance = mock_instance(CONTRACT, &[]);
@@ -608,6 +862,36 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn call_execute_reports_entry_point_on_deserialization_error() {
+        let wasm = bump_allocator_contract("", GARBAGE_RESULT_CONTRACT);
+        let mut instance = mock_instance(&wasm, &[]);
+
+        let info = mock_info("creator", &[]);
+        let err =
+            call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, b"{}").unwrap_err();
+        match err {
+            VmError::DeserializeErr {
+                entry_point,
+                length,
+                target_type,
+                ..
+            } => {
+                assert_eq!(entry_point, "execute");
+                assert_eq!(length, 5);
+                assert_eq!(
+                    target_type,
+                    "cosmwasm_std::results::contract_result::ContractResult<cosmwasm_std::results::response::Response>"
+                );
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // the raw call still returns the data as is
+        let data = call_execute_raw(&mut instance, b"{}", b"{}", b"{}").unwrap();
+        assert_eq!(data, b"\xff{foo");
+    }
+
     #[test]
     fn call_execute_works() {
         let mut instance = mock_instance(CONTRACT, &[]);
@@ -627,6 +911,262 @@ mod tests {
             .unwrap();
     }
 
+    #[test]
+    fn call_instantiate_respects_max_instantiate_msg_len() {
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+
+        // boundary
+        let mut instance = mock_instance_with_options(
+            CONTRACT,
+            MockInstanceOptions {
+                instance_options: InstanceOptions {
+                    max_instantiate_msg_len: Some(msg.len()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        // too big
+        let mut instance = mock_instance_with_options(
+            CONTRACT,
+            MockInstanceOptions {
+                instance_options: InstanceOptions {
+                    max_instantiate_msg_len: Some(msg.len() - 1),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let err =
+            call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg).unwrap_err();
+        match err {
+            VmError::CommunicationErr {
+                source: CommunicationError::RegionLengthTooBig { length, max_length },
+                ..
+            } => {
+                assert_eq!(length, msg.len());
+                assert_eq!(max_length, msg.len() - 1);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+        // the contract was not called
+        assert_eq!(instance.get_gas_left(), instance.create_gas_report().limit);
+    }
+
+    #[test]
+    fn call_execute_respects_max_execute_msg_len() {
+        let msg = br#"{"release":{}}"#;
+        for (max_execute_msg_len, should_pass) in [(msg.len(), true), (msg.len() - 1, false)] {
+            let mut instance = mock_instance_with_options(
+                CONTRACT,
+                MockInstanceOptions {
+                    instance_options: InstanceOptions {
+                        max_execute_msg_len: Some(max_execute_msg_len),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+
+            // the instantiate message is not affected by the execute limit
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let init_msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, init_msg)
+                .unwrap()
+                .unwrap();
+
+            let info = mock_info("verifies", &coins(15, "earth"));
+            let result = call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg);
+            if should_pass {
+                result.unwrap().unwrap();
+            } else {
+                match result.unwrap_err() {
+                    VmError::CommunicationErr {
+                        source: CommunicationError::RegionLengthTooBig { length, max_length },
+                        ..
+                    } => {
+                        assert_eq!(length, msg.len());
+                        assert_eq!(max_length, max_execute_msg_len);
+                    }
+                    err => panic!("Unexpected error: {:?}", err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn call_execute_applies_msg_filter() {
+        let filter = MsgFilter::new(|msg| match msg {
+            CosmosMsg::Bank(_) => Err("bank messages are not allowed".to_string()),
+            _ => Ok(()),
+        });
+        let mut instance = mock_instance_with_options(
+            CONTRACT,
+            MockInstanceOptions {
+                contract_balance: Some(&coins(1000, "earth")),
+                instance_options: InstanceOptions {
+                    msg_filter: Some(filter),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        // init
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        // a response without messages passes
+        let info = mock_info("anyone", &[]);
+        let msg = br#"{"user_errors_in_api_calls":{}}"#;
+        let response = call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+        assert_eq!(response.messages.len(), 0);
+
+        // release sends all funds to the beneficiary
+        let info = mock_info("verifies", &[]);
+        let msg = br#"{"release":{}}"#;
+        let err =
+            call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg).unwrap_err();
+        match err {
+            VmError::MessageRejected { msg, .. } => {
+                assert_eq!(msg, "bank messages are not allowed")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        // the raw call is not filtered
+        call_execute_raw(
+            &mut instance,
+            &to_vec(&mock_env()).unwrap(),
+            &to_vec(&info).unwrap(),
+            msg,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn check_messages_passes_custom_payloads() {
+        #[derive(Serialize, Clone, Debug, PartialEq, JsonSchema)]
+        struct SpecialMsg {
+            special: String,
+        }
+        impl CustomMsg for SpecialMsg {}
+
+        let filter = MsgFilter::new(|msg| match msg {
+            CosmosMsg::Custom(payload) if payload.as_slice() == br#"{"special":"forbidden"}"# => {
+                Err("forbidden custom message".to_string())
+            }
+            _ => Ok(()),
+        });
+
+        let response = Response::new().add_message(CosmosMsg::Custom(SpecialMsg {
+            special: "allowed".to_string(),
+        }));
+        check_messages(&filter, &response).unwrap();
+
+        let response = response.add_message(CosmosMsg::Custom(SpecialMsg {
+            special: "forbidden".to_string(),
+        }));
+        match check_messages(&filter, &response).unwrap_err() {
+            VmError::MessageRejected { msg, .. } => assert_eq!(msg, "forbidden custom message"),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn call_execute_respects_max_messages_per_response() {
+        for (max_messages_per_response, should_pass) in [(1, true), (0, false)] {
+            let mut instance = mock_instance_with_options(
+                CONTRACT,
+                MockInstanceOptions {
+                    contract_balance: Some(&coins(1000, "earth")),
+                    instance_options: InstanceOptions {
+                        max_messages_per_response: Some(max_messages_per_response),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+
+            // init
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+
+            // release returns one bank message
+            let info = mock_info("verifies", &[]);
+            let msg = br#"{"release":{}}"#;
+            let result = call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg);
+            if should_pass {
+                assert_eq!(result.unwrap().unwrap().messages.len(), 1);
+            } else {
+                match result.unwrap_err() {
+                    VmError::MessageRejected { msg, .. } => assert_eq!(
+                        msg,
+                        "Response contains 1 messages, which exceeds the limit of 0"
+                    ),
+                    err => panic!("Unexpected error: {:?}", err),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn call_execute_respects_auto_tag_events() {
+        for auto_tag_events in [true, false] {
+            let mut instance = mock_instance_with_options(
+                CONTRACT,
+                MockInstanceOptions {
+                    contract_balance: Some(&coins(1000, "earth")),
+                    instance_options: InstanceOptions {
+                        auto_tag_events,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+            );
+
+            // init
+            let info = mock_info("creator", &coins(1000, "earth"));
+            let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+            call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+
+            // release emits a "hackatom" event
+            let info = mock_info("verifies", &[]);
+            let msg = br#"{"release":{}}"#;
+            let response = call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+            assert_eq!(response.events.len(), 1);
+            let attributes = &response.events[0].attributes;
+            if auto_tag_events {
+                assert_eq!(attributes.len(), 2);
+                assert_eq!(attributes[0].key, "_contract_address");
+                assert_eq!(attributes[0].value, mock_env().contract.address.as_str());
+                assert_eq!(attributes[1].key, "action");
+            } else {
+                assert_eq!(attributes.len(), 1);
+                assert_eq!(attributes[0].key, "action");
+            }
+            // top level attributes are never tagged
+            assert_eq!(response.attributes[0].key, "action");
+        }
+    }
+
     #[test]
     fn call_migrate_works() {
         let mut instance = mock_instance(CONTRACT, &[]);
@@ -652,6 +1192,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn call_migrate_with_info_works() {
+        // migrate only succeeds if called by "admin"
+        let wasm = bump_allocator_contract(
+            "",
+            r#"
+                (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 256))
+                ;; succeeds if the info equals the data at 128
+                (func (export "migrate") (param $env i32) (param $info i32) (param $msg i32) (result i32)
+                    (local $data i32)
+                    (local $i i32)
+                    (if (i32.ne (i32.load offset=8 (local.get $info)) (i32.const 29))
+                        (then (return (i32.const 512))))
+                    (local.set $data (i32.load (local.get $info)))
+                    (block $done
+                        (loop $next_byte
+                            (br_if $done (i32.eq (local.get $i) (i32.const 29)))
+                            (if (i32.ne
+                                    (i32.load8_u (i32.add (local.get $data) (local.get $i)))
+                                    (i32.load8_u (i32.add (i32.const 128) (local.get $i))))
+                                (then (return (i32.const 512))))
+                            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                            (br $next_byte)))
+                    (i32.const 256))
+                (data (i32.const 128) "{\"sender\":\"admin\",\"funds\":[]}")
+                ;; Region { offset: 272, capacity: 62, length: 62 }
+                (data (i32.const 256) "\10\01\00\00\3e\00\00\00\3e\00\00\00")
+                (data (i32.const 272) "{\"ok\":{\"messages\":[],\"attributes\":[],\"events\":[],\"data\":null}}")
+                ;; Region { offset: 528, capacity: 24, length: 24 }
+                (data (i32.const 512) "\10\02\00\00\18\00\00\00\18\00\00\00")
+                (data (i32.const 528) "{\"error\":\"unauthorized\"}")
+            "#,
+        );
+        let mut instance = mock_instance(&wasm, &[]);
+
+        let info = mock_info("admin", &[]);
+        call_migrate_with_info::<_, _, _, Empty>(&mut instance, &mock_env(), &info, b"{}")
+            .unwrap()
+            .unwrap();
+
+        let info = mock_info("someone else", &[]);
+        let err =
+            call_migrate_with_info::<_, _, _, Empty>(&mut instance, &mock_env(), &info, b"{}")
+                .unwrap()
+                .unwrap_err();
+        assert_eq!(err, "unauthorized");
+    }
+
+    #[test]
+    fn call_migrate_with_info_works_for_migrate_without_info() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+
+        // init
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        // the info is not passed to a migrate entry point that does not take it
+        let info = mock_info("creator", &[]);
+        let msg = br#"{"verifier": "someone else"}"#;
+        call_migrate_with_info::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+
+        let msg = br#"{"verifier":{}}"#;
+        let contract_result = call_query(&mut instance, &mock_env(), msg).unwrap();
+        assert_eq!(
+            contract_result.unwrap().as_slice(),
+            b"{\"verifier\":\"someone else\"}"
+        );
+    }
+
     #[test]
     fn call_query_works() {
         let mut instance = mock_instance(CONTRACT, &[]);
@@ -670,6 +1284,180 @@ mod tests {
         assert_eq!(query_response.as_slice(), b"{\"verifier\":\"verifies\"}");
     }
 
+    #[test]
+    fn call_raw_provides_block_info() {
+        // execute traps unless the block matches mock_env()
+        let wasm = bump_allocator_contract(
+            r#"
+                (import "env" "get_block_time" (func $get_block_time (result i64)))
+                (import "env" "get_block_height" (func $get_block_height (result i64)))
+            "#,
+            r#"
+                (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 256))
+                (func (export "execute") (param i32 i32 i32) (result i32)
+                    (if (i64.ne (call $get_block_height) (i64.const 12345))
+                        (then unreachable))
+                    (if (i64.ne (call $get_block_time) (i64.const 1571797419879305533))
+                        (then unreachable))
+                    (i32.const 256))
+                ;; Region { offset: 512, capacity: 0, length: 0 }
+                (data (i32.const 256) "\00\02\00\00\00\00\00\00\00\00\00\00")
+            "#,
+        );
+        let mut instance = mock_instance(&wasm, &[]);
+        let env = mock_env();
+        assert_eq!(env.block.height, 12345);
+        assert_eq!(env.block.time.nanos(), 1571797419879305533);
+
+        let info = to_vec(&mock_info("creator", &[])).unwrap();
+        call_execute_raw(&mut instance, &to_vec(&env).unwrap(), &info, b"{}").unwrap();
+
+        // a different block fails
+        let mut other_env = mock_env();
+        other_env.block.height += 1;
+        match call_execute_raw(&mut instance, &to_vec(&other_env).unwrap(), &info, b"{}")
+            .unwrap_err()
+        {
+            VmError::RuntimeErr { msg, .. } => assert!(msg.contains("unreachable")),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn call_raw_explains_unreachable_trap() {
+        // execute prints a panic message and traps like a contract built without the abort feature
+        let wasm = bump_allocator_contract(
+            r#"
+                (import "env" "debug" (func $debug (param i32)))
+            "#,
+            r#"
+                (func (export "instantiate") (param i32 i32 i32) (result i32) unreachable)
+                (func (export "execute") (param i32 i32 i32) (result i32)
+                    (call $debug (i32.const 256))
+                    unreachable)
+                ;; Region { offset: 272, capacity: 19, length: 19 }
+                (data (i32.const 256) "\10\01\00\00\13\00\00\00\13\00\00\00")
+                (data (i32.const 272) "panicked at 'oh no'")
+            "#,
+        );
+        let env = to_vec(&mock_env()).unwrap();
+        let info = to_vec(&mock_info("creator", &[])).unwrap();
+
+        // with debug printing the panic message is shown
+        let mut instance = mock_instance(&wasm, &[]);
+        match call_execute_raw(&mut instance, &env, &info, b"{}").unwrap_err() {
+            VmError::RuntimeErr { msg, .. } => assert_eq!(
+                msg,
+                "Wasmer runtime error: RuntimeError: unreachable. Contract likely panicked. Last debug message: panicked at 'oh no'"
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // the message does not leak into the next call
+        match call_instantiate_raw(&mut instance, &env, &info, b"{}").unwrap_err() {
+            VmError::RuntimeErr { msg, .. } => assert_eq!(
+                msg,
+                "Wasmer runtime error: RuntimeError: unreachable. Contract likely panicked."
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // without debug printing there is a hint
+        let mut instance = mock_instance_with_options(
+            &wasm,
+            MockInstanceOptions {
+                print_debug: false,
+                ..Default::default()
+            },
+        );
+        match call_execute_raw(&mut instance, &env, &info, b"{}").unwrap_err() {
+            VmError::RuntimeErr { msg, .. } => assert_eq!(
+                msg,
+                "Wasmer runtime error: RuntimeError: unreachable. Contract likely panicked; enable debug to see the message."
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn calls_set_storage_readonly_flag() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        assert_storage_writable(&mut instance, |instance| {
+            call_instantiate::<_, _, _, Empty>(instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+        });
+
+        // query leaves the storage readonly, even if it was writable before
+        let msg = br#"{"verifier":{}}"#;
+        assert_storage_readonly(&mut instance, |instance| {
+            call_query(instance, &mock_env(), msg).unwrap().unwrap();
+        });
+
+        // execute makes it writable again
+        let info = mock_info("verifies", &coins(15, "earth"));
+        let msg = br#"{"release":{}}"#;
+        assert_storage_writable(&mut instance, |instance| {
+            call_execute::<_, _, _, Empty>(instance, &mock_env(), &info, msg)
+                .unwrap()
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn call_resets_gas_for_every_call() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+
+        let info = mock_info("creator", &coins(1000, "earth"));
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        call_instantiate::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
+            .unwrap()
+            .unwrap();
+        let instantiate_report = instance.create_gas_report();
+        assert!(instantiate_report.used_externally > 0);
+
+        let msg = br#"{"verifier":{}}"#;
+        call_query(&mut instance, &mock_env(), msg)
+            .unwrap()
+            .unwrap();
+        let report1 = instance.create_gas_report();
+        assert_eq!(report1.limit, instantiate_report.limit);
+        assert!(report1.used_externally > 0);
+        assert_eq!(
+            report1.remaining,
+            report1.limit - report1.used_externally - report1.used_internally
+        );
+
+        // the second call starts with the full budget and no externally used gas
+        call_query(&mut instance, &mock_env(), msg)
+            .unwrap()
+            .unwrap();
+        let report2 = instance.create_gas_report();
+        assert_eq!(report2.used_externally, report1.used_externally);
+        assert_eq!(report2.used_internally, report1.used_internally);
+        assert_eq!(report2.remaining, report1.remaining);
+    }
+
+    #[test]
+    fn call_reply_fails_for_missing_export() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let gas_before = instance.get_gas_left();
+
+        let reply = Reply {
+            id: 1,
+            result: SubMsgResult::Err("boom".to_string()),
+        };
+        match call_reply::<_, _, _, Empty>(&mut instance, &mock_env(), &reply).unwrap_err() {
+            VmError::MissingEntryPoint { entry_point, .. } => assert_eq!(entry_point, "reply"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+        // nothing was executed in the contract
+        assert_eq!(instance.get_gas_left(), gas_before);
+    }
+
     #[cfg(feature = "stargate")]
     mod ibc {
         use super::*;
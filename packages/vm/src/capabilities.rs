@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
-use crate::static_analysis::ExportInfo;
+use crate::errors::VmResult;
+use crate::static_analysis::{deserialize_wasm, ExportInfo};
 
 const REQUIRES_PREFIX: &str = "requires_";
 
@@ -30,10 +31,34 @@ pub fn required_capabilities_from_module(module: &impl ExportInfo) -> HashSet<St
         .collect()
 }
 
+/// The well-known capabilities a contract can require via `requires_*` marker exports
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ContractFeatures {
+    pub iterator: bool,
+    pub staking: bool,
+    pub stargate: bool,
+    pub cosmwasm_1_1: bool,
+    pub cosmwasm_1_3: bool,
+}
+
+/// Returns which of the well-known capabilities the given Wasm requires,
+/// e.g. to display a contract's feature set in tooling.
+/// Unknown capabilities are ignored. See [`crate::AnalysisReport::required_capabilities`] for all of them.
+pub fn contract_feature_exports(wasm: &[u8]) -> VmResult<ContractFeatures> {
+    let module = deserialize_wasm(wasm)?;
+    let required = required_capabilities_from_module(&module);
+    Ok(ContractFeatures {
+        iterator: required.contains("iterator"),
+        staking: required.contains("staking"),
+        stargate: required.contains("stargate"),
+        cosmwasm_1_1: required.contains("cosmwasm_1_1"),
+        cosmwasm_1_3: required.contains("cosmwasm_1_3"),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::static_analysis::deserialize_wasm;
 
     #[test]
     fn capabilities_from_csv_works() {
@@ -89,4 +114,44 @@ mod tests {
         let required_capabilities = required_capabilities_from_module(&module);
         assert_eq!(required_capabilities.len(), 0);
     }
+
+    #[test]
+    fn contract_feature_exports_works() {
+        let features =
+            contract_feature_exports(include_bytes!("../testdata/hackatom.wasm")).unwrap();
+        assert_eq!(features, ContractFeatures::default());
+
+        let features =
+            contract_feature_exports(include_bytes!("../testdata/ibc_reflect.wasm")).unwrap();
+        assert_eq!(
+            features,
+            ContractFeatures {
+                iterator: true,
+                staking: true,
+                stargate: true,
+                cosmwasm_1_1: false,
+                cosmwasm_1_3: false,
+            }
+        );
+
+        let wasm = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (export "requires_cosmwasm_1_1" (func 0))
+            (export "requires_cosmwasm_1_3" (func 0))
+            (export "requires_water" (func 0))
+        )"#,
+        )
+        .unwrap();
+        let features = contract_feature_exports(&wasm).unwrap();
+        assert_eq!(
+            features,
+            ContractFeatures {
+                cosmwasm_1_1: true,
+                cosmwasm_1_3: true,
+                ..ContractFeatures::default()
+            }
+        );
+    }
 }
@@ -1,5 +1,5 @@
-use parity_wasm::elements::Type;
 use parity_wasm::elements::{External, ImportEntry, Module};
+use parity_wasm::elements::{FunctionType, ImportCountType, Internal, Type, ValueType};
 use std::collections::BTreeSet;
 use std::collections::HashSet;
 
@@ -13,6 +13,7 @@ use crate::static_analysis::{deserialize_wasm, ExportInfo};
 const SUPPORTED_IMPORTS: &[&str] = &[
     "env.abort",
     "env.db_read",
+    "env.db_read_range",
     "env.db_write",
     "env.db_remove",
     "env.addr_validate",
@@ -24,6 +25,8 @@ const SUPPORTED_IMPORTS: &[&str] = &[
     "env.ed25519_batch_verify",
     "env.sha1_calculate",
     "env.debug",
+    "env.get_block_time",
+    "env.get_block_height",
     "env.query_chain",
     #[cfg(feature = "iterator")]
     "env.db_scan",
@@ -43,9 +46,23 @@ const REQUIRED_EXPORTS: &[&str] = &[
     "instantiate",
 ];
 
+/// Signatures (params, results) of the exports the VM calls to manage contract memory.
+/// A mismatch would only surface as a runtime error when the VM allocates or frees a Region.
+const IO_EXPORT_SIGNATURES: &[(&str, &[ValueType], &[ValueType])] = &[
+    ("allocate", &[ValueType::I32], &[ValueType::I32]),
+    ("deallocate", &[ValueType::I32], &[]),
+];
+
+/// Signature of the optional "reply" entry point, which receives the results of submessages.
+/// Submessages are part of all supported interface versions, so only the signature is checked.
+const REPLY_SIGNATURE: (&[ValueType], &[ValueType]) =
+    (&[ValueType::I32, ValueType::I32], &[ValueType::I32]);
+
 const INTERFACE_VERSION_PREFIX: &str = "interface_version_";
-const SUPPORTED_INTERFACE_VERSIONS: &[&str] = &[
-    "interface_version_8",
+const SUPPORTED_INTERFACE_VERSIONS: &[&str] = &["interface_version_8"];
+/// Interface versions that can still be accepted but produce a warning in [`check_wasm_report`].
+/// The `allow_interface_version_7` feature is the upper bound of what can be accepted at runtime.
+const LEGACY_INTERFACE_VERSIONS: &[&str] = &[
     #[cfg(feature = "allow_interface_version_7")]
     "interface_version_7",
 ];
@@ -58,20 +75,159 @@ const MAX_FUNCTION_PARAMS: usize = 50;
 
 const MAX_FUNCTION_RESULTS: usize = 1;
 
+/// Default upper bound for the summed up length of all data segments in bytes, see
+/// [`crate::CacheOptions::max_data_segments_size`].
+/// This is far more than real contracts need.
+pub const DEFAULT_MAX_DATA_SEGMENTS_SIZE: usize = 16 * 1024 * 1024; // 16 MiB
+
 /// Checks if the data is valid wasm and compatibility with the CosmWasm API (imports and exports)
+///
+/// Legacy interface versions are rejected, like in [`crate::Cache::save_wasm`] with the default
+/// [`crate::CacheOptions::accept_legacy_interface_versions`]. Use [`check_wasm_with_legacy_toggle`]
+/// to check against a cache that accepts them.
 pub fn check_wasm(wasm_code: &[u8], available_capabilities: &HashSet<String>) -> VmResult<()> {
+    check_wasm_with_imports(wasm_code, available_capabilities, &[])
+}
+
+/// Like [`check_wasm`] but accepts the imports in `extra_imports` (e.g. "env.my_host_fn")
+/// in addition to the ones supported by this crate. This allows hosts to provide custom
+/// functions to contracts.
+pub fn check_wasm_with_imports(
+    wasm_code: &[u8],
+    available_capabilities: &HashSet<String>,
+    extra_imports: &[&str],
+) -> VmResult<()> {
+    check_wasm_impl(
+        wasm_code,
+        available_capabilities,
+        extra_imports,
+        false,
+        DEFAULT_MAX_DATA_SEGMENTS_SIZE,
+    )
+}
+
+/// Like [`check_wasm`] but accepts legacy interface versions enabled via the
+/// `allow_interface_version_7` feature if `accept_legacy_interface_versions` is set.
+pub fn check_wasm_with_legacy_toggle(
+    wasm_code: &[u8],
+    available_capabilities: &HashSet<String>,
+    accept_legacy_interface_versions: bool,
+) -> VmResult<()> {
+    check_wasm_impl(
+        wasm_code,
+        available_capabilities,
+        &[],
+        accept_legacy_interface_versions,
+        DEFAULT_MAX_DATA_SEGMENTS_SIZE,
+    )
+}
+
+/// The checks run by [`crate::Cache::save_wasm`], configured by the cache options
+pub(crate) fn check_wasm_for_cache(
+    wasm_code: &[u8],
+    available_capabilities: &HashSet<String>,
+    accept_legacy_interface_versions: bool,
+    max_data_segments_size: usize,
+) -> VmResult<()> {
+    check_wasm_impl(
+        wasm_code,
+        available_capabilities,
+        &[],
+        accept_legacy_interface_versions,
+        max_data_segments_size,
+    )
+}
+
+fn check_wasm_impl(
+    wasm_code: &[u8],
+    available_capabilities: &HashSet<String>,
+    extra_imports: &[&str],
+    accept_legacy_interface_versions: bool,
+    max_data_segments_size: usize,
+) -> VmResult<()> {
     let module = deserialize_wasm(wasm_code)?;
     check_wasm_memories(&module)?;
-    check_interface_version(&module)?;
+    check_wasm_start_function(&module)?;
+    check_interface_version(&module, accept_legacy_interface_versions)?;
     check_wasm_exports(&module)?;
-    check_wasm_imports(&module, SUPPORTED_IMPORTS)?;
+    check_wasm_io_signatures(&module)?;
+    check_wasm_reply(&module)?;
+    if extra_imports.is_empty() {
+        check_wasm_imports(&module, SUPPORTED_IMPORTS)?;
+    } else {
+        let supported_imports: Vec<&str> = SUPPORTED_IMPORTS
+            .iter()
+            .chain(extra_imports.iter())
+            .copied()
+            .collect();
+        check_wasm_imports(&module, &supported_imports)?;
+    }
     check_wasm_capabilities(&module, available_capabilities)?;
     check_wasm_functions(&module)?;
+    check_wasm_data_segments(&module, max_data_segments_size)?;
 
     Ok(())
 }
 
+/// The result of [`check_wasm_report`]
+#[derive(Debug, Default)]
+pub struct ValidationReport {
+    /// Issues that make the contract incompatible with this VM. [`check_wasm`] fails
+    /// with the first of them.
+    pub errors: Vec<VmError>,
+    /// Issues that do not prevent the contract from running. It is up to the host
+    /// whether to log or reject them.
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Returns true if no errors were found. Warnings are not taken into account.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Runs the same checks as [`check_wasm`] but collects all errors instead of failing on
+/// the first one, along with warnings about soft issues (like deprecated interface versions).
+/// Legacy interface versions enabled at compile time are reported as warnings, not errors.
+///
+/// An error is only returned if the data cannot be deserialized as a Wasm module.
+pub fn check_wasm_report(
+    wasm_code: &[u8],
+    available_capabilities: &HashSet<String>,
+) -> VmResult<ValidationReport> {
+    let module = deserialize_wasm(wasm_code)?;
+    let mut report = ValidationReport::default();
+    let results = [
+        check_wasm_memories(&module),
+        check_wasm_start_function(&module),
+        check_interface_version(&module, true),
+        check_wasm_exports(&module),
+        check_wasm_io_signatures(&module),
+        check_wasm_reply(&module),
+        check_wasm_imports(&module, SUPPORTED_IMPORTS),
+        check_wasm_capabilities(&module, available_capabilities),
+        check_wasm_functions(&module),
+        check_wasm_data_segments(&module, DEFAULT_MAX_DATA_SEGMENTS_SIZE),
+    ];
+    report.errors = results.into_iter().filter_map(Result::err).collect();
+    report.warnings = interface_version_warnings(&module);
+    Ok(report)
+}
+
 fn check_wasm_memories(module: &Module) -> VmResult<()> {
+    // The host expects a single memory defined and exported by the contract
+    let imports_memory = module
+        .import_section()
+        .into_iter()
+        .flat_map(|section| section.entries())
+        .any(|entry| matches!(entry.external(), External::Memory(_)));
+    if imports_memory {
+        return Err(VmError::static_validation_err(
+            "Wasm contract must not import a memory",
+        ));
+    }
+
     let section = match module.memory_section() {
         Some(section) => section,
         None => {
@@ -107,7 +263,18 @@ fn check_wasm_memories(module: &Module) -> VmResult<()> {
     Ok(())
 }
 
-fn check_interface_version(module: &Module) -> VmResult<()> {
+/// The host controls the lifecycle of a contract. A start function would run code
+/// during instantiation of the Wasm instance, outside of any entry point.
+fn check_wasm_start_function(module: &Module) -> VmResult<()> {
+    if module.start_section().is_some() {
+        return Err(VmError::static_validation_err(
+            "Wasm contract must not have a start function",
+        ));
+    }
+    Ok(())
+}
+
+fn check_interface_version(module: &Module, accept_legacy: bool) -> VmResult<()> {
     let mut interface_version_exports = module
         .exported_function_names(Some(INTERFACE_VERSION_PREFIX))
         .into_iter();
@@ -119,10 +286,8 @@ fn check_interface_version(module: &Module) -> VmResult<()> {
         } else {
             // Exactly one interface version found
             let version_str = first_interface_version_export.as_str();
-            if SUPPORTED_INTERFACE_VERSIONS
-                .iter()
-                .any(|&v| v == version_str)
-            {
+            let is_legacy = LEGACY_INTERFACE_VERSIONS.contains(&version_str);
+            if SUPPORTED_INTERFACE_VERSIONS.contains(&version_str) || (accept_legacy && is_legacy) {
                 Ok(())
             } else {
                 Err(VmError::static_validation_err(
@@ -137,6 +302,20 @@ fn check_interface_version(module: &Module) -> VmResult<()> {
     }
 }
 
+fn interface_version_warnings(module: &Module) -> Vec<String> {
+    module
+        .exported_function_names(Some(INTERFACE_VERSION_PREFIX))
+        .into_iter()
+        .filter(|name| LEGACY_INTERFACE_VERSIONS.contains(&name.as_str()))
+        .map(|name| {
+            format!(
+                "Wasm contract uses deprecated marker export: {}. Please upgrade to interface_version_8.",
+                name
+            )
+        })
+        .collect()
+}
+
 fn check_wasm_exports(module: &Module) -> VmResult<()> {
     let available_exports: HashSet<String> = module.exported_function_names(None);
     for required_export in REQUIRED_EXPORTS {
@@ -150,6 +329,88 @@ fn check_wasm_exports(module: &Module) -> VmResult<()> {
     Ok(())
 }
 
+/// Checks that the memory management exports have the signatures the VM calls them with.
+/// Missing exports are reported by [`check_wasm_exports`].
+fn check_wasm_io_signatures(module: &Module) -> VmResult<()> {
+    for (name, params, results) in IO_EXPORT_SIGNATURES {
+        if let Some(function_type) = exported_function_type(module, name) {
+            if function_type.params() != *params || function_type.results() != *results {
+                return Err(VmError::static_validation_err(format!(
+                    "Wasm contract export \"{}\" has an invalid signature. Expected {}, got {}.",
+                    name,
+                    signature_to_string(params, results),
+                    signature_to_string(function_type.params(), function_type.results()),
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the optional "reply" export can be called with (env, msg) Region pointers
+/// and returns a Region pointer. Otherwise the error would only show up when a submessage
+/// result is dispatched to the contract.
+fn check_wasm_reply(module: &Module) -> VmResult<()> {
+    let (params, results) = REPLY_SIGNATURE;
+    if let Some(function_type) = exported_function_type(module, "reply") {
+        if function_type.params() != params || function_type.results() != results {
+            return Err(VmError::static_validation_err(format!(
+                "Wasm contract export \"reply\" has an invalid signature. Expected {}, got {}.",
+                signature_to_string(params, results),
+                signature_to_string(function_type.params(), function_type.results()),
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the type of the exported function with the given name, if any
+fn exported_function_type<'a>(module: &'a Module, name: &str) -> Option<&'a FunctionType> {
+    let function_index = module
+        .export_section()?
+        .entries()
+        .iter()
+        .find_map(|entry| match entry.internal() {
+            Internal::Function(index) if entry.field() == name => Some(*index as usize),
+            _ => None,
+        })?;
+
+    // The function index space starts with the imported functions
+    let imported_count = module.import_count(ImportCountType::Function);
+    let type_index = if function_index < imported_count {
+        module
+            .import_section()?
+            .entries()
+            .iter()
+            .filter_map(|entry| match entry.external() {
+                External::Function(type_index) => Some(*type_index),
+                _ => None,
+            })
+            .nth(function_index)?
+    } else {
+        module
+            .function_section()?
+            .entries()
+            .get(function_index - imported_count)?
+            .type_ref()
+    };
+
+    match module.type_section()?.types().get(type_index as usize)? {
+        Type::Function(function_type) => Some(function_type),
+    }
+}
+
+fn signature_to_string(params: &[ValueType], results: &[ValueType]) -> String {
+    let join = |types: &[ValueType]| {
+        types
+            .iter()
+            .map(|t| t.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    format!("({}) -> ({})", join(params), join(results))
+}
+
 /// Checks if the import requirements of the contract are satisfied.
 /// When this is not the case, we either have an incompatibility between contract and VM
 /// or a error in the contract.
@@ -163,9 +424,13 @@ fn check_wasm_imports(module: &Module, supported_imports: &[&str]) -> VmResult<(
     for required_import in required_imports {
         let full_name = full_import_name(&required_import);
         if !supported_imports.contains(&full_name.as_str()) {
+            let suggestion = match closest_import(&full_name, supported_imports) {
+                Some(closest) => format!(" Did you mean \"{}\"?", closest),
+                None => String::new(),
+            };
             return Err(VmError::static_validation_err(format!(
-                "Wasm contract requires unsupported import: \"{}\". Required imports: {}. Available imports: {:?}.",
-                full_name, required_import_names.to_string_limited(200), supported_imports
+                "Wasm contract requires unsupported import: \"{}\". Required imports: {}. Available imports: {:?}.{}",
+                full_name, required_import_names.to_string_limited(200), supported_imports, suggestion
             )));
         }
 
@@ -184,6 +449,37 @@ fn full_import_name(ie: &ImportEntry) -> String {
     format!("{}.{}", ie.module(), ie.field())
 }
 
+/// Max edit distance between an unsupported import and a supported one to suggest the latter
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Returns the supported import closest to `name` if it is close enough to be a typo
+fn closest_import<'a>(name: &str, supported_imports: &[&'a str]) -> Option<&'a str> {
+    supported_imports
+        .iter()
+        .map(|supported| (levenshtein(name, supported), *supported))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, supported)| supported)
+}
+
+/// The number of single character insertions, deletions and substitutions
+/// needed to turn `a` into `b`
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // distances from the processed prefix of `a` to all prefixes of `b`
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
 fn check_wasm_capabilities(
     module: &Module,
     available_capabilities: &HashSet<String>,
@@ -247,6 +543,27 @@ fn check_wasm_functions(module: &Module) -> VmResult<()> {
     Ok(())
 }
 
+/// Rejects contracts whose data segments are larger than `max_size` bytes in total.
+/// Those are copied into memory on instantiation.
+fn check_wasm_data_segments(module: &Module, max_size: usize) -> VmResult<()> {
+    let total_size = module
+        .data_section()
+        .map(|ds| ds.entries())
+        .unwrap_or_default()
+        .iter()
+        .fold(0usize, |acc, segment| {
+            acc.saturating_add(segment.value().len())
+        });
+
+    if total_size > max_size {
+        return Err(VmError::static_validation_err(format!(
+            "Wasm contract's data segments exceed the limit of {} bytes (got {} bytes)",
+            max_size, total_size
+        )));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -275,6 +592,74 @@ mod tests {
         check_wasm(CONTRACT_RUST_170, &default_capabilities()).unwrap();
     }
 
+    /// Inserts a DataCount section, which must come right before the code and data sections
+    fn with_data_count_section(wasm: &[u8], count: u8) -> Vec<u8> {
+        let mut pos = 8; // magic and version
+        while pos < wasm.len() && wasm[pos] != 10 && wasm[pos] != 11 {
+            // section id followed by the LEB128 encoded section length
+            let mut len = 0usize;
+            let mut shift = 0;
+            pos += 1;
+            loop {
+                let byte = wasm[pos];
+                pos += 1;
+                len |= ((byte & 0x7f) as usize) << shift;
+                shift += 7;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+            }
+            pos += len;
+        }
+        let mut out = wasm[..pos].to_vec();
+        out.extend_from_slice(&[12, 1, count]);
+        out.extend_from_slice(&wasm[pos..]);
+        out
+    }
+
+    #[test]
+    fn check_wasm_allows_data_count_section() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_8" (func 0))
+            (export "allocate" (func 1))
+            (export "deallocate" (func 2))
+            (export "instantiate" (func 0))
+            (data (i32.const 1024) "cosmwasm")
+        )"#,
+        )
+        .unwrap();
+        let wasm = with_data_count_section(&wasm, 1);
+        let module = deserialize_wasm(&wasm).unwrap();
+        assert!(module
+            .sections()
+            .iter()
+            .any(|section| matches!(section, parity_wasm::elements::Section::DataCount(1))));
+        check_wasm(&wasm, &default_capabilities()).unwrap();
+
+        // bulk memory operations are not supported though
+        let wasm = wat::parse_str(
+            r#"(module
+            (memory 3)
+            (data "cosmwasm")
+            (func data.drop 0)
+        )"#,
+        )
+        .unwrap();
+        match check_wasm(&wasm, &default_capabilities()).unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(msg.starts_with("Wasm bytecode could not be deserialized."))
+            }
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
     #[test]
     fn check_wasm_old_contract() {
         match check_wasm(CONTRACT_0_15, &default_capabilities()) {
@@ -314,6 +699,123 @@ mod tests {
         };
     }
 
+    #[test]
+    fn check_wasm_with_legacy_toggle_works() {
+        let wasm_v7 = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_7" (func 0))
+            (export "allocate" (func 1))
+            (export "deallocate" (func 2))
+            (export "instantiate" (func 0))
+        )"#,
+        )
+        .unwrap();
+
+        // check_wasm behaves like a cache with the default options
+        check_wasm(&wasm_v7, &default_capabilities()).unwrap_err();
+        check_wasm_with_legacy_toggle(&wasm_v7, &default_capabilities(), false).unwrap_err();
+
+        let result = check_wasm_with_legacy_toggle(&wasm_v7, &default_capabilities(), true);
+        #[cfg(feature = "allow_interface_version_7")]
+        result.unwrap();
+        #[cfg(not(feature = "allow_interface_version_7"))]
+        result.unwrap_err();
+
+        // current contracts pass independent of the toggle
+        check_wasm_with_legacy_toggle(CONTRACT, &default_capabilities(), false).unwrap();
+        check_wasm_with_legacy_toggle(CONTRACT, &default_capabilities(), true).unwrap();
+    }
+
+    #[test]
+    fn check_wasm_with_imports_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (import "env" "my_custom_host_fn" (func (param i32) (result i32)))
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_8" (func 1))
+            (export "allocate" (func 2))
+            (export "deallocate" (func 3))
+            (export "instantiate" (func 1))
+        )"#,
+        )
+        .unwrap();
+
+        check_wasm_with_imports(&wasm, &default_capabilities(), &["env.my_custom_host_fn"])
+            .unwrap();
+
+        match check_wasm(&wasm, &default_capabilities()).unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => assert!(msg.starts_with(
+                "Wasm contract requires unsupported import: \"env.my_custom_host_fn\""
+            )),
+            e => panic!("Unexpected error {:?}", e),
+        }
+        match check_wasm_with_imports(&wasm, &default_capabilities(), &["env.other_host_fn"])
+            .unwrap_err()
+        {
+            VmError::StaticValidationErr { msg, .. } => assert!(msg.starts_with(
+                "Wasm contract requires unsupported import: \"env.my_custom_host_fn\""
+            )),
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn check_wasm_report_works() {
+        let report = check_wasm_report(CONTRACT, &default_capabilities()).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.warnings, Vec::<String>::new());
+
+        // all errors are collected
+        let report = check_wasm_report(CONTRACT_0_7, &HashSet::new()).unwrap();
+        assert!(!report.is_valid());
+        assert!(report.errors.len() > 1);
+        assert!(report
+            .errors
+            .iter()
+            .all(|e| matches!(e, VmError::StaticValidationErr { .. })));
+
+        // invalid Wasm is a hard error
+        check_wasm_report(&[0x00, 0x61, 0x73], &default_capabilities()).unwrap_err();
+
+        let wasm_v7 = wat::parse_str(
+            r#"(module
+            (type (func))
+            (func (type 0) nop)
+            (func (param i32) (result i32) local.get 0)
+            (func (param i32))
+            (memory 3)
+            (export "memory" (memory 0))
+            (export "interface_version_7" (func 0))
+            (export "allocate" (func 1))
+            (export "deallocate" (func 2))
+            (export "instantiate" (func 0))
+        )"#,
+        )
+        .unwrap();
+        let report = check_wasm_report(&wasm_v7, &default_capabilities()).unwrap();
+        #[cfg(feature = "allow_interface_version_7")]
+        {
+            assert!(report.is_valid());
+            assert_eq!(report.warnings, ["Wasm contract uses deprecated marker export: interface_version_7. Please upgrade to interface_version_8."]);
+        }
+        #[cfg(not(feature = "allow_interface_version_7"))]
+        {
+            assert_eq!(report.errors.len(), 1);
+            assert_eq!(report.warnings, Vec::<String>::new());
+        }
+    }
+
     #[test]
     fn check_wasm_memories_ok() {
         let wasm = wat::parse_str("(module (memory 1))").unwrap();
@@ -377,6 +879,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_memories_imported_memory() {
+        let wasm = wat::parse_str(r#"(module (import "env" "memory" (memory 1)))"#).unwrap();
+        match check_wasm_memories(&deserialize_wasm(&wasm).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => {
+                assert_eq!(msg, "Wasm contract must not import a memory");
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with imported memory"),
+        }
+    }
+
     #[test]
     fn check_wasm_memories_initial_size() {
         let wasm_ok = wat::parse_str("(module (memory 512))").unwrap();
@@ -404,6 +918,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_data_segments_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (memory 1)
+            (data (i32.const 0) "0123456789")
+            (data (i32.const 64) "abcdef")
+        )"#,
+        )
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        check_wasm_data_segments(&module, DEFAULT_MAX_DATA_SEGMENTS_SIZE).unwrap();
+        // at the cap
+        check_wasm_data_segments(&module, 16).unwrap();
+
+        // above the cap
+        match check_wasm_data_segments(&module, 15) {
+            Err(VmError::StaticValidationErr { msg, .. }) => {
+                assert_eq!(
+                    msg,
+                    "Wasm contract's data segments exceed the limit of 15 bytes (got 16 bytes)"
+                );
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with too large data segments"),
+        }
+
+        // no data section
+        let wasm = wat::parse_str("(module (memory 1))").unwrap();
+        check_wasm_data_segments(&deserialize_wasm(&wasm).unwrap(), 0).unwrap();
+    }
+
+    #[test]
+    fn check_wasm_rejects_huge_data_segments() {
+        let data = "a".repeat(DEFAULT_MAX_DATA_SEGMENTS_SIZE / 2 + 1);
+        let wasm = wat::parse_str(format!(
+            r#"(module
+            (memory 512)
+            (data (i32.const 0) "{data}")
+            (data (i32.const 0) "{data}")
+        )"#
+        ))
+        .unwrap();
+        let module = deserialize_wasm(&wasm).unwrap();
+        match check_wasm_data_segments(&module, DEFAULT_MAX_DATA_SEGMENTS_SIZE) {
+            Err(VmError::StaticValidationErr { msg, .. }) => {
+                assert!(msg.starts_with("Wasm contract's data segments exceed the limit"));
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with huge data segments"),
+        }
+
+        // also reported by check_wasm_report
+        let report = check_wasm_report(&wasm, &default_capabilities()).unwrap();
+        assert!(report.errors.iter().any(|e| e
+            .to_string()
+            .contains("Wasm contract's data segments exceed the limit")));
+    }
+
+    #[test]
+    fn check_wasm_start_function_works() {
+        let wasm = wat::parse_str("(module (func) (memory 1))").unwrap();
+        check_wasm_start_function(&deserialize_wasm(&wasm).unwrap()).unwrap();
+
+        let wasm_start = wat::parse_str("(module (func) (memory 1) (start 0))").unwrap();
+        match check_wasm_start_function(&deserialize_wasm(&wasm_start).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => {
+                assert_eq!(msg, "Wasm contract must not have a start function");
+            }
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with start function"),
+        }
+    }
+
     #[test]
     fn check_interface_version_works() {
         // valid
@@ -420,7 +1008,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        check_interface_version(&module).unwrap();
+        check_interface_version(&module, true).unwrap();
 
         #[cfg(feature = "allow_interface_version_7")]
         {
@@ -438,7 +1026,16 @@ mod tests {
             )
             .unwrap();
             let module = deserialize_wasm(&wasm).unwrap();
-            check_interface_version(&module).unwrap();
+            check_interface_version(&module, true).unwrap();
+            // legacy versions can be disabled at runtime
+            match check_interface_version(&module, false).unwrap_err() {
+                VmError::StaticValidationErr { msg, .. } => {
+                    assert!(msg.starts_with(
+                        "Wasm contract has unknown interface_version_* marker export"
+                    ));
+                }
+                err => panic!("Unexpected error {:?}", err),
+            }
         }
 
         // missing
@@ -454,7 +1051,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module, true).unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(
                     msg,
@@ -479,7 +1076,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module, true).unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(
                     msg,
@@ -503,7 +1100,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module, true).unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(msg, "Wasm contract has unknown interface_version_* marker export (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)");
             }
@@ -524,7 +1121,7 @@ mod tests {
         )
         .unwrap();
         let module = deserialize_wasm(&wasm).unwrap();
-        match check_interface_version(&module).unwrap_err() {
+        match check_interface_version(&module, true).unwrap_err() {
             VmError::StaticValidationErr { msg, .. } => {
                 assert_eq!(msg, "Wasm contract has unknown interface_version_* marker export (see https://github.com/CosmWasm/cosmwasm/blob/main/packages/vm/README.md)");
             }
@@ -589,6 +1186,107 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_io_signatures_works() {
+        // valid
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "allocate") (param i32) (result i32) local.get 0)
+                (func (export "deallocate") (param i32))
+            )"#,
+        )
+        .unwrap();
+        check_wasm_io_signatures(&deserialize_wasm(&wasm).unwrap()).unwrap();
+
+        // missing exports are not checked here
+        let wasm = wat::parse_str("(module)").unwrap();
+        check_wasm_io_signatures(&deserialize_wasm(&wasm).unwrap()).unwrap();
+
+        // deallocate with wrong signature
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "allocate") (param i32) (result i32) local.get 0)
+                (func (export "deallocate") (param i32 i32))
+            )"#,
+        )
+        .unwrap();
+        match check_wasm_io_signatures(&deserialize_wasm(&wasm).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract export \"deallocate\" has an invalid signature. Expected (i32) -> (), got (i32, i32) -> ()."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with invalid deallocate"),
+        }
+
+        // allocate with wrong signature
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "allocate") (param i64) (result i64) local.get 0)
+                (func (export "deallocate") (param i32))
+            )"#,
+        )
+        .unwrap();
+        match check_wasm_io_signatures(&deserialize_wasm(&wasm).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract export \"allocate\" has an invalid signature. Expected (i32) -> (i32), got (i64) -> (i64)."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with invalid allocate"),
+        }
+
+        // re-exported import with wrong signature
+        let wasm = wat::parse_str(
+            r#"(module
+                (import "env" "debug" (func $debug (param i32)))
+                (export "allocate" (func $debug))
+                (func (export "deallocate") (param i32))
+            )"#,
+        )
+        .unwrap();
+        match check_wasm_io_signatures(&deserialize_wasm(&wasm).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract export \"allocate\" has an invalid signature. Expected (i32) -> (i32), got (i32) -> ()."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with invalid allocate"),
+        }
+    }
+
+    #[test]
+    fn check_wasm_reply_works() {
+        // without reply
+        let wasm = wat::parse_str("(module)").unwrap();
+        check_wasm_reply(&deserialize_wasm(&wasm).unwrap()).unwrap();
+
+        // with reply
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "reply") (param i32 i32) (result i32) local.get 0)
+            )"#,
+        )
+        .unwrap();
+        check_wasm_reply(&deserialize_wasm(&wasm).unwrap()).unwrap();
+
+        // reply with wrong signature
+        let wasm = wat::parse_str(
+            r#"(module
+                (func (export "reply") (param i32) (result i32) local.get 0)
+            )"#,
+        )
+        .unwrap();
+        match check_wasm_reply(&deserialize_wasm(&wasm).unwrap()) {
+            Err(VmError::StaticValidationErr { msg, .. }) => assert_eq!(
+                msg,
+                "Wasm contract export \"reply\" has an invalid signature. Expected (i32, i32) -> (i32), got (i32) -> (i32)."
+            ),
+            Err(e) => panic!("Unexpected error {:?}", e),
+            Ok(_) => panic!("Didn't reject wasm with invalid reply"),
+        }
+    }
+
     #[test]
     fn check_wasm_exports_of_old_contract() {
         let module = deserialize_wasm(CONTRACT_0_7).unwrap();
@@ -665,6 +1363,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_wasm_imports_suggests_closest_import() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (import "env" "db_read" (func (param i32) (result i32)))
+            (import "env" "db_reed" (func (param i32) (result i32)))
+        )"#,
+        )
+        .unwrap();
+        let result = check_wasm_imports(&deserialize_wasm(&wasm).unwrap(), SUPPORTED_IMPORTS);
+        match result.unwrap_err() {
+            VmError::StaticValidationErr { msg, .. } => {
+                assert!(msg.starts_with(
+                    r#"Wasm contract requires unsupported import: "env.db_reed". Required imports: {"env.db_read", "env.db_reed"}. Available imports: ["#
+                ));
+                assert!(
+                    msg.ends_with(r#"]. Did you mean "env.db_read"?"#),
+                    "{}",
+                    msg
+                );
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn closest_import_works() {
+        let supported: &[&str] = &["env.db_read", "env.db_write", "env.debug"];
+        assert_eq!(
+            closest_import("env.db_reed", supported),
+            Some("env.db_read")
+        );
+        assert_eq!(
+            closest_import("env.db_writ", supported),
+            Some("env.db_write")
+        );
+        assert_eq!(closest_import("env.debu", supported), Some("env.debug"));
+        assert_eq!(closest_import("env.foo", supported), None);
+        assert_eq!(closest_import("env.read_db", supported), None);
+        assert_eq!(closest_import("env.db_read", &[]), None);
+    }
+
+    #[test]
+    fn levenshtein_works() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("abc", "abc"), 0);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("db_reed", "db_read"), 1);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+    }
+
     #[test]
     fn check_wasm_imports_of_old_contract() {
         let module = deserialize_wasm(CONTRACT_0_7).unwrap();
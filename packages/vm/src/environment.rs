@@ -1,13 +1,23 @@
 //! Internal details to be used by instance.rs only
 use std::borrow::{Borrow, BorrowMut};
+#[cfg(feature = "iterator")]
+use std::collections::HashSet;
+use std::collections::{BTreeSet, HashMap};
 use std::ptr::NonNull;
 use std::sync::{Arc, RwLock};
 
+use cosmwasm_std::BlockInfo;
+use serde::Deserialize;
 use wasmer::{HostEnvInitError, Instance as WasmerInstance, Memory, Val, WasmerEnv};
 use wasmer_middlewares::metering::{get_remaining_points, set_remaining_points, MeteringPoints};
+use wasmer_types::TrapCode;
 
 use crate::backend::{BackendApi, GasInfo, Querier, Storage};
-use crate::errors::{VmError, VmResult};
+use crate::errors::{CommunicationError, VmError, VmResult};
+use crate::serde::from_slice;
+
+/// Max length (in bytes) of the serialized env parsed by [`Environment::block_info`]
+const MAX_LENGTH_ENV: usize = 64 * 1024;
 
 /// Never can never be instantiated.
 /// Replace this with the [never primitive type](https://doc.rust-lang.org/std/primitive.never.html) when stable.
@@ -31,6 +41,13 @@ pub struct GasConfig {
     pub ed25519_batch_verify_one_pubkey_cost: u64,
     /// sha1 hash calculation cost (single input)
     pub sha1_calculate_cost: u64,
+    /// cost of reading the block time or height of the current call
+    pub block_info_cost: u64,
+    /// address canonicalize/humanize cost per input byte, charged for the VM side
+    /// validation and conversion before calling the backend
+    pub addr_conversion_cost: u64,
+    /// minimum cost of every import call, charged once per call such that no import is free
+    pub min_import_cost: u64,
 }
 
 impl Default for GasConfig {
@@ -50,6 +67,12 @@ impl Default for GasConfig {
             ed25519_batch_verify_cost: 63 * GAS_PER_US / 2,
             ed25519_batch_verify_one_pubkey_cost: 63 * GAS_PER_US / 4,
             sha1_calculate_cost: 269 * GAS_PER_NS,
+            // reads a cached value after the env was parsed once
+            block_info_cost: 100 * GAS_PER_NS,
+            // UTF-8 validation and copying is in the order of a nanosecond per byte
+            addr_conversion_cost: GAS_PER_NS,
+            // a small floor that does not affect imports with a nonzero cost
+            min_import_cost: 1,
         }
     }
 }
@@ -59,7 +82,7 @@ impl Default for GasConfig {
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct GasState {
     /// Gas limit for the computation, including internally and externally used gas.
-    /// This is set when the Environment is created and never mutated.
+    /// This is set when the Environment is created and only changed by [`Environment::reset_gas`].
     ///
     /// Measured in [CosmWasm gas](https://github.com/CosmWasm/cosmwasm/blob/main/docs/GAS.md).
     pub gas_limit: u64,
@@ -82,6 +105,21 @@ pub struct Environment<A: BackendApi, S: Storage, Q: Querier> {
     pub api: A,
     pub print_debug: bool,
     pub gas_config: GasConfig,
+    /// An upper bound for the number of bytes copied out of a single Region by the VM.
+    /// `None` means no additional limit.
+    pub max_region_read_len: Option<usize>,
+    /// Factor applied to all gas charged via `process_gas_info` and `decrease_gas_left`.
+    pub gas_multiplier: u64,
+    /// Max batch size for ed25519_batch_verify. `None` means the built-in limit is used.
+    pub max_ed25519_batch_size: Option<usize>,
+    /// Max number of bytes allocated in the contract by the VM per call. `None` means no limit.
+    pub max_vm_allocations_bytes: Option<usize>,
+    /// When set, gas reported by the querier is not charged.
+    pub free_queries: bool,
+    /// Max number of `db_next` calls per iterator. `None` means no limit.
+    pub max_iterator_steps: Option<usize>,
+    /// Max length of a serialized query response written into the contract. `None` means no limit.
+    pub max_query_response_len: Option<usize>,
     data: Arc<RwLock<ContextData<S, Q>>>,
 }
 
@@ -95,6 +133,13 @@ impl<A: BackendApi, S: Storage, Q: Querier> Clone for Environment<A, S, Q> {
             api: self.api,
             print_debug: self.print_debug,
             gas_config: self.gas_config.clone(),
+            max_region_read_len: self.max_region_read_len,
+            gas_multiplier: self.gas_multiplier,
+            max_ed25519_batch_size: self.max_ed25519_batch_size,
+            max_vm_allocations_bytes: self.max_vm_allocations_bytes,
+            free_queries: self.free_queries,
+            max_iterator_steps: self.max_iterator_steps,
+            max_query_response_len: self.max_query_response_len,
             data: self.data.clone(),
         }
     }
@@ -112,6 +157,13 @@ impl<A: BackendApi, S: Storage, Q: Querier> Environment<A, S, Q> {
             api,
             print_debug,
             gas_config: GasConfig::default(),
+            max_region_read_len: None,
+            gas_multiplier: 1,
+            max_ed25519_batch_size: None,
+            max_vm_allocations_bytes: None,
+            free_queries: false,
+            max_iterator_steps: None,
+            max_query_response_len: None,
             data: Arc::new(RwLock::new(ContextData::new(gas_limit))),
         }
     }
@@ -172,17 +224,59 @@ impl<A: BackendApi, S: Storage, Q: Querier> Environment<A, S, Q> {
             Ok(func.clone())
         })?;
         func.call(args).map_err(|runtime_err| -> VmError {
-            self.with_wasmer_instance::<_, Never>(|instance| {
-                let err: VmError = match get_remaining_points(instance) {
-                    MeteringPoints::Remaining(_) => VmError::from(runtime_err),
-                    MeteringPoints::Exhausted => VmError::gas_depletion(),
-                };
-                Err(err)
-            })
-            .unwrap_err() // with_wasmer_instance can only succeed if the callback succeeds
+            let unreachable =
+                runtime_err.clone().to_trap() == Some(TrapCode::UnreachableCodeReached);
+            let err = self
+                .with_wasmer_instance::<_, Never>(|instance| {
+                    let err: VmError = match get_remaining_points(instance) {
+                        MeteringPoints::Remaining(_) => VmError::from(runtime_err),
+                        MeteringPoints::Exhausted => VmError::gas_depletion(),
+                    };
+                    Err(err)
+                })
+                .unwrap_err(); // with_wasmer_instance can only succeed if the callback succeeds
+            if unreachable {
+                self.explain_unreachable(err)
+            } else {
+                err
+            }
         })
     }
 
+    /// Contracts built without the `abort` feature of cosmwasm-std turn a panic into an
+    /// `unreachable` trap, which says nothing about the cause. This adds a hint to such
+    /// errors and, if debug printing is enabled, the last message printed by the contract.
+    /// Must only be called for errors created from an `unreachable` trap.
+    fn explain_unreachable(&self, mut err: VmError) -> VmError {
+        if let VmError::RuntimeErr { msg, .. } = &mut err {
+            let last_debug_message =
+                self.with_context_data_mut(|context_data| context_data.last_debug_message.take());
+            match (self.print_debug, last_debug_message) {
+                (true, Some(debug_msg)) => {
+                    msg.push_str(&format!(
+                        ". Contract likely panicked. Last debug message: {}",
+                        debug_msg
+                    ));
+                }
+                (true, None) => msg.push_str(". Contract likely panicked."),
+                (false, _) => {
+                    msg.push_str(". Contract likely panicked; enable debug to see the message.")
+                }
+            }
+        }
+        err
+    }
+
+    /// Stores the message printed by the contract via `debug`, which is used to explain traps.
+    pub(crate) fn set_last_debug_message(&self, msg: String) {
+        self.with_context_data_mut(|context_data| context_data.last_debug_message = Some(msg))
+    }
+
+    /// Forgets the last debug message. Called at the start of each call.
+    pub(crate) fn reset_last_debug_message(&self) {
+        self.with_context_data_mut(|context_data| context_data.last_debug_message = None)
+    }
+
     pub fn call_function0(&self, name: &str, args: &[Val]) -> VmResult<()> {
         let result = self.call_function(name, args)?;
         let expected = 0;
@@ -259,11 +353,19 @@ impl<A: BackendApi, S: Storage, Q: Querier> Environment<A, S, Q> {
         .expect("Wasmer instance is not set. This is a bug in the lifecycle.")
     }
 
-    /// Decreases gas left by the given amount.
+    /// Starts a fresh gas budget of `gas_limit`, e.g. for the next call on a reused instance.
+    /// This resets both the gas left in Wasmer and the externally used gas.
+    pub fn reset_gas(&self, gas_limit: u64) {
+        self.with_gas_state_mut(|gas_state| *gas_state = GasState::with_limit(gas_limit));
+        self.set_gas_left(gas_limit);
+    }
+
+    /// Decreases gas left by the given amount, scaled by the gas multiplier.
     /// If the amount exceeds the available gas, the remaining gas is set to 0 and
     /// an VmError::GasDepletion error is returned.
     #[allow(unused)] // used in tests
     pub fn decrease_gas_left(&self, amount: u64) -> VmResult<()> {
+        let amount = self.apply_gas_multiplier(amount);
         self.with_wasmer_instance(|instance| {
             let remaining = match get_remaining_points(instance) {
                 MeteringPoints::Remaining(count) => count,
@@ -279,6 +381,217 @@ impl<A: BackendApi, S: Storage, Q: Querier> Environment<A, S, Q> {
         })
     }
 
+    /// Starts recording the gas consumed by each import, discarding previous records.
+    pub(crate) fn start_import_gas_tracking(&self) {
+        self.with_context_data_mut(|context_data| {
+            context_data.import_gas = Some(HashMap::new());
+        })
+    }
+
+    /// Stops recording and returns the gas consumed by each import since
+    /// tracking was started.
+    pub(crate) fn stop_import_gas_tracking(&self) -> HashMap<&'static str, u64> {
+        self.with_context_data_mut(|context_data| context_data.import_gas.take())
+            .unwrap_or_default()
+    }
+
+    /// Returns the gas left at the start of an import call.
+    pub(crate) fn import_gas_checkpoint(&self) -> u64 {
+        self.get_gas_left()
+    }
+
+    /// Charges the part of `min_import_cost` that the import started at `checkpoint`
+    /// did not consume yet, such that no import call is free.
+    pub(crate) fn charge_min_import_cost(&self, checkpoint: u64) -> VmResult<()> {
+        let gas_left = self.get_gas_left();
+        let used = checkpoint.saturating_sub(gas_left);
+        let missing = self.gas_config.min_import_cost.saturating_sub(used);
+        if missing == 0 {
+            return Ok(());
+        }
+        if missing > gas_left {
+            self.set_gas_left(0);
+            return Err(VmError::gas_depletion());
+        }
+        self.set_gas_left(gas_left - missing);
+        Ok(())
+    }
+
+    /// Adds the gas consumed since `checkpoint` to the total of `import` if import gas is tracked.
+    /// This includes gas used by the contract's allocator when the import writes a result.
+    pub(crate) fn record_import_gas(&self, import: &'static str, checkpoint: u64) {
+        let used = checkpoint.saturating_sub(self.get_gas_left());
+        self.with_context_data_mut(|context_data| {
+            if let Some(import_gas) = context_data.import_gas.as_mut() {
+                *import_gas.entry(import).or_insert(0) += used;
+            }
+        })
+    }
+
+    /// Sets the serialized env of the current call. It is only parsed when needed.
+    pub(crate) fn set_serialized_env(&self, serialized_env: &[u8]) {
+        self.with_context_data_mut(|context_data| {
+            context_data.serialized_env = Some(serialized_env.to_vec());
+            context_data.block_info = None;
+        })
+    }
+
+    /// Returns the block of the current call's env. The env is parsed on first use.
+    pub(crate) fn block_info(&self) -> VmResult<BlockInfo> {
+        self.with_context_data_mut(|context_data| {
+            if let Some(block_info) = &context_data.block_info {
+                return Ok(block_info.clone());
+            }
+            let serialized_env = context_data
+                .serialized_env
+                .as_ref()
+                .ok_or_else(|| VmError::generic_err("No env set for the current call"))?;
+
+            #[derive(Deserialize)]
+            struct EnvBlock {
+                block: BlockInfo,
+            }
+            let EnvBlock { block } = from_slice(serialized_env, MAX_LENGTH_ENV)?;
+            context_data.block_info = Some(block.clone());
+            Ok(block)
+        })
+    }
+
+    /// Starts recording the storage keys accessed by the contract, discarding previous records.
+    pub(crate) fn enable_access_list(&self) {
+        self.with_context_data_mut(|context_data| {
+            context_data.access_list = Some(AccessList::default());
+        })
+    }
+
+    /// Returns the keys read and written since the access list was enabled, each sorted and
+    /// without duplicates. Both are empty if the access list is not enabled.
+    pub(crate) fn access_list(&self) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        self.with_context_data(|context_data| match &context_data.access_list {
+            Some(list) => (
+                list.reads.iter().cloned().collect(),
+                list.writes.iter().cloned().collect(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        })
+    }
+
+    /// Adds `key` to the keys read if the access list is enabled
+    pub(crate) fn record_read(&self, key: &[u8]) {
+        self.with_context_data_mut(|context_data| {
+            if let Some(list) = context_data.access_list.as_mut() {
+                list.reads.insert(key.to_vec());
+            }
+        })
+    }
+
+    /// Adds `key` to the keys written if the access list is enabled.
+    /// Removals count as writes.
+    pub(crate) fn record_write(&self, key: &[u8]) {
+        self.with_context_data_mut(|context_data| {
+            if let Some(list) = context_data.access_list.as_mut() {
+                list.writes.insert(key.to_vec());
+            }
+        })
+    }
+
+    /// Resets the number of bytes allocated by the VM. Called at the start of each call.
+    pub(crate) fn reset_vm_allocated_bytes(&self) {
+        self.with_context_data_mut(|context_data| context_data.vm_allocated_bytes = 0)
+    }
+
+    /// Adds `size` to the number of bytes allocated by the VM in the current call
+    /// and checks the result against `max_vm_allocations_bytes`.
+    pub(crate) fn record_vm_allocation(&self, size: usize) -> VmResult<()> {
+        self.with_context_data_mut(|context_data| {
+            let total = context_data.vm_allocated_bytes.saturating_add(size);
+            if let Some(limit) = self.max_vm_allocations_bytes {
+                if total > limit {
+                    return Err(
+                        CommunicationError::vm_allocations_limit_exceeded(total, limit).into(),
+                    );
+                }
+            }
+            context_data.vm_allocated_bytes = total;
+            Ok(())
+        })
+    }
+
+    /// Forgets all iterators. Backends create new iterator ids for every call,
+    /// so this is called at the start of each call.
+    #[cfg(feature = "iterator")]
+    pub(crate) fn reset_iterators(&self) {
+        self.with_context_data_mut(|context_data| {
+            context_data.iterators = IteratorState::default();
+        })
+    }
+
+    /// Registers an iterator id returned by the backend. Ids must increase within a call,
+    /// otherwise a new iterator could be confused with an older one.
+    #[cfg(feature = "iterator")]
+    pub(crate) fn record_new_iterator(&self, iterator_id: u32) -> VmResult<()> {
+        self.with_context_data_mut(|context_data| {
+            let iterators = &mut context_data.iterators;
+            if let Some(last_id) = iterators.last_id {
+                if iterator_id <= last_id {
+                    return Err(VmError::generic_err(format!(
+                        "Backend returned iterator id {} after id {}. Iterator ids must increase within a call.",
+                        iterator_id, last_id
+                    )));
+                }
+            }
+            iterators.last_id = Some(iterator_id);
+            Ok(())
+        })
+    }
+
+    /// Marks the iterator as exhausted after it returned no more elements
+    #[cfg(feature = "iterator")]
+    pub(crate) fn mark_iterator_exhausted(&self, iterator_id: u32) {
+        self.with_context_data_mut(|context_data| {
+            context_data.iterators.exhausted.insert(iterator_id);
+        })
+    }
+
+    /// Returns true iff the iterator returned no more elements before
+    #[cfg(feature = "iterator")]
+    pub(crate) fn is_iterator_exhausted(&self, iterator_id: u32) -> bool {
+        self.with_context_data(|context_data| {
+            context_data.iterators.exhausted.contains(&iterator_id)
+        })
+    }
+
+    /// Counts a step of the given iterator and checks the result against `max_iterator_steps`
+    #[cfg(feature = "iterator")]
+    pub(crate) fn record_iterator_step(&self, iterator_id: u32) -> VmResult<()> {
+        self.with_context_data_mut(|context_data| {
+            let steps = context_data.iterators.steps.entry(iterator_id).or_insert(0);
+            *steps = steps.saturating_add(1);
+            if let Some(limit) = self.max_iterator_steps {
+                if *steps > limit {
+                    return Err(
+                        CommunicationError::iterator_steps_exceeded(iterator_id, limit).into(),
+                    );
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Scales gas charged by the host according to the gas multiplier
+    pub fn apply_gas_multiplier(&self, amount: u64) -> u64 {
+        amount.saturating_mul(self.gas_multiplier)
+    }
+
+    /// Returns the maximum length for reading a Region, which is the given `max_length`
+    /// capped by `max_region_read_len` if set.
+    pub fn region_read_limit(&self, max_length: usize) -> usize {
+        match self.max_region_read_len {
+            Some(cap) => max_length.min(cap),
+            None => max_length,
+        }
+    }
+
     pub fn memory(&self) -> Memory {
         self.with_wasmer_instance(|instance| {
             let first: Option<Memory> = instance
@@ -323,6 +636,38 @@ pub struct ContextData<S: Storage, Q: Querier> {
     querier: Option<Q>,
     /// A non-owning link to the wasmer instance
     wasmer_instance: Option<NonNull<WasmerInstance>>,
+    /// Gas consumed per import. `None` unless import gas tracking is enabled.
+    import_gas: Option<HashMap<&'static str, u64>>,
+    /// Number of bytes allocated in the contract by the VM since the current call started
+    vm_allocated_bytes: usize,
+    /// Storage keys accessed by the contract. `None` unless the access list is enabled.
+    access_list: Option<AccessList>,
+    /// The iterators created in the current call
+    #[cfg(feature = "iterator")]
+    iterators: IteratorState,
+    /// The env passed to the current call
+    serialized_env: Option<Vec<u8>>,
+    /// The block of `serialized_env`, once parsed
+    block_info: Option<BlockInfo>,
+    /// The last message printed via `debug` in the current call
+    last_debug_message: Option<String>,
+}
+
+#[derive(Default)]
+struct AccessList {
+    reads: BTreeSet<Vec<u8>>,
+    writes: BTreeSet<Vec<u8>>,
+}
+
+#[cfg(feature = "iterator")]
+#[derive(Default)]
+struct IteratorState {
+    /// The most recent iterator id returned by the backend
+    last_id: Option<u32>,
+    /// Number of `db_next` calls per iterator id
+    steps: HashMap<u32, usize>,
+    /// Iterators that returned no more elements
+    exhausted: HashSet<u32>,
 }
 
 impl<S: Storage, Q: Querier> ContextData<S, Q> {
@@ -333,6 +678,14 @@ impl<S: Storage, Q: Querier> ContextData<S, Q> {
             storage_readonly: true,
             querier: None,
             wasmer_instance: None,
+            import_gas: None,
+            vm_allocated_bytes: 0,
+            access_list: None,
+            #[cfg(feature = "iterator")]
+            iterators: IteratorState::default(),
+            serialized_env: None,
+            block_info: None,
+            last_debug_message: None,
         }
     }
 }
@@ -341,10 +694,16 @@ pub fn process_gas_info<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     info: GasInfo,
 ) -> VmResult<()> {
+    let info = GasInfo::new(
+        env.apply_gas_multiplier(info.cost),
+        env.apply_gas_multiplier(info.externally_used),
+    );
     let gas_left = env.get_gas_left();
 
     let new_limit = env.with_gas_state_mut(|gas_state| {
-        gas_state.externally_used_gas += info.externally_used;
+        gas_state.externally_used_gas = gas_state
+            .externally_used_gas
+            .saturating_add(info.externally_used);
         // These lines reduce the amount of gas available to wasmer
         // so it can not consume gas that was consumed externally.
         gas_left
@@ -355,10 +714,10 @@ pub fn process_gas_info<A: BackendApi, S: Storage, Q: Querier>(
     // This tells wasmer how much more gas it can consume from this point in time.
     env.set_gas_left(new_limit);
 
-    if info.externally_used + info.cost > gas_left {
-        Err(VmError::gas_depletion())
-    } else {
-        Ok(())
+    match info.externally_used.checked_add(info.cost) {
+        Some(total_used) if total_used <= gas_left => Ok(()),
+        // An overflow means more gas than could ever be available was used
+        _ => Err(VmError::gas_depletion()),
     }
 }
 
@@ -488,6 +847,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn charge_min_import_cost_works() {
+        let (mut env, _instance) = make_instance(100);
+        env.gas_config.min_import_cost = 10;
+        assert_eq!(env.get_gas_left(), 100);
+
+        // a free call is charged the floor
+        let checkpoint = env.import_gas_checkpoint();
+        env.charge_min_import_cost(checkpoint).unwrap();
+        assert_eq!(env.get_gas_left(), 90);
+        let externally_used = env.with_gas_state(|gas_state| gas_state.externally_used_gas);
+        assert_eq!(externally_used, 0);
+
+        // the floor is topped up to include externally used gas
+        let checkpoint = env.import_gas_checkpoint();
+        process_gas_info(&env, GasInfo::new(1, 4)).unwrap();
+        env.charge_min_import_cost(checkpoint).unwrap();
+        assert_eq!(env.get_gas_left(), 80);
+
+        // costs above the floor are unchanged
+        let checkpoint = env.import_gas_checkpoint();
+        process_gas_info(&env, GasInfo::with_cost(15)).unwrap();
+        env.charge_min_import_cost(checkpoint).unwrap();
+        assert_eq!(env.get_gas_left(), 65);
+
+        // running out of gas for the floor fails
+        env.gas_config.min_import_cost = 100;
+        let checkpoint = env.import_gas_checkpoint();
+        match env.charge_min_import_cost(checkpoint).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(env.get_gas_left(), 0);
+    }
+
     #[test]
     fn process_gas_info_works_for_externally_used() {
         let (env, _instance) = make_instance(100);
@@ -557,6 +951,28 @@ mod tests {
         assert_eq!(gas_state.externally_used_gas, 75);
     }
 
+    #[test]
+    fn process_gas_info_does_not_overflow() {
+        let (env, _instance) = make_instance(100);
+
+        match process_gas_info(&env, GasInfo::new(u64::MAX, 1)).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(env.get_gas_left(), 0);
+        let gas_state = env.with_gas_state(|gas_state| gas_state.clone());
+        assert_eq!(gas_state.externally_used_gas, 1);
+
+        // externally used gas saturates
+        match process_gas_info(&env, GasInfo::new(0, u64::MAX)).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+        assert_eq!(env.get_gas_left(), 0);
+        let gas_state = env.with_gas_state(|gas_state| gas_state.clone());
+        assert_eq!(gas_state.externally_used_gas, u64::MAX);
+    }
+
     #[test]
     fn process_gas_info_zeros_gas_left_when_exceeded() {
         // with_externally_used
@@ -615,6 +1031,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn process_gas_info_applies_gas_multiplier() {
+        let (mut env, _instance) = make_instance(1000);
+        env.gas_multiplier = 2;
+
+        process_gas_info(&env, GasInfo::with_externally_used(50)).unwrap();
+        assert_eq!(env.get_gas_left(), 900);
+        process_gas_info(&env, GasInfo::with_cost(70)).unwrap();
+        assert_eq!(env.get_gas_left(), 760);
+        env.with_gas_state(|gas_state| {
+            assert_eq!(gas_state.externally_used_gas, 100);
+        });
+
+        env.decrease_gas_left(30).unwrap();
+        assert_eq!(env.get_gas_left(), 700);
+
+        match process_gas_info(&env, GasInfo::with_cost(351)).unwrap_err() {
+            VmError::GasDepletion { .. } => {}
+            err => panic!("unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn is_storage_readonly_defaults_to_true() {
         let (env, _instance) = make_instance(TESTING_GAS_LIMIT);
@@ -7,6 +7,15 @@ use super::region_validation_error::RegionValidationError;
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum CommunicationError {
+    #[error("Batch too large. Got {}, limit {}", size, max_size)]
+    BatchTooLarge { size: usize, max_size: usize },
+    /// A value returned by the storage backend is longer than the contract is allowed to write
+    #[error(
+        "Value read from storage too long. Got {}, limit {}",
+        length,
+        max_length
+    )]
+    DbValueTooLong { length: usize, max_length: usize },
     #[error(
         "The Wasm memory address {} provided by the contract could not be dereferenced: {}",
         offset,
@@ -27,16 +36,39 @@ pub enum CommunicationError {
     /// Whenever UTF-8 bytes cannot be decoded into a unicode string, e.g. in String::from_utf8 or str::from_utf8.
     #[error("Cannot decode UTF8 bytes into string: {}", msg)]
     InvalidUtf8 { msg: String },
+    #[error(
+        "Iterator {} was stepped more often than the limit of {} steps",
+        iterator_id,
+        max_steps
+    )]
+    IteratorStepsExceeded { iterator_id: u32, max_steps: usize },
+    /// A serialized query response is longer than the host is allowed to write into the contract
+    #[error("Query response too long. Got {}, limit {}", length, max_length)]
+    QueryResponseTooLong { length: usize, max_length: usize },
     #[error("Region length too big. Got {}, limit {}", length, max_length)]
     // Note: this only checks length, not capacity
     RegionLengthTooBig { length: usize, max_length: usize },
     #[error("Region too small. Got {}, required {}", size, required)]
     RegionTooSmall { size: usize, required: usize },
+    #[error(
+        "Total size of regions allocated by the VM exceeds the limit. Got {}, limit {}",
+        size,
+        max_size
+    )]
+    VmAllocationsLimitExceeded { size: usize, max_size: usize },
     #[error("Got a zero Wasm address")]
     ZeroAddress {},
 }
 
 impl CommunicationError {
+    pub(crate) fn batch_too_large(size: usize, max_size: usize) -> Self {
+        CommunicationError::BatchTooLarge { size, max_size }
+    }
+
+    pub(crate) fn db_value_too_long(length: usize, max_length: usize) -> Self {
+        CommunicationError::DbValueTooLong { length, max_length }
+    }
+
     pub(crate) fn deref_err(offset: u32, msg: impl Into<String>) -> Self {
         CommunicationError::DerefErr {
             offset,
@@ -56,6 +88,18 @@ impl CommunicationError {
         }
     }
 
+    #[cfg(feature = "iterator")]
+    pub(crate) fn iterator_steps_exceeded(iterator_id: u32, max_steps: usize) -> Self {
+        CommunicationError::IteratorStepsExceeded {
+            iterator_id,
+            max_steps,
+        }
+    }
+
+    pub(crate) fn query_response_too_long(length: usize, max_length: usize) -> Self {
+        CommunicationError::QueryResponseTooLong { length, max_length }
+    }
+
     pub(crate) fn region_length_too_big(length: usize, max_length: usize) -> Self {
         CommunicationError::RegionLengthTooBig { length, max_length }
     }
@@ -64,6 +108,10 @@ impl CommunicationError {
         CommunicationError::RegionTooSmall { size, required }
     }
 
+    pub(crate) fn vm_allocations_limit_exceeded(size: usize, max_size: usize) -> Self {
+        CommunicationError::VmAllocationsLimitExceeded { size, max_size }
+    }
+
     pub(crate) fn zero_address() -> Self {
         CommunicationError::ZeroAddress {}
     }
@@ -75,6 +123,32 @@ mod tests {
 
     // constructors
 
+    #[test]
+    fn batch_too_large_works() {
+        let error = CommunicationError::batch_too_large(300, 256);
+        match error {
+            CommunicationError::BatchTooLarge { size, max_size, .. } => {
+                assert_eq!(size, 300);
+                assert_eq!(max_size, 256);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn db_value_too_long_works() {
+        let error = CommunicationError::db_value_too_long(1025, 1024);
+        match error {
+            CommunicationError::DbValueTooLong {
+                length, max_length, ..
+            } => {
+                assert_eq!(length, 1025);
+                assert_eq!(max_length, 1024);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn deref_err() {
         let error = CommunicationError::deref_err(345, "broken stuff");
@@ -105,6 +179,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn query_response_too_long_works() {
+        let error = CommunicationError::query_response_too_long(2049, 2048);
+        match error {
+            CommunicationError::QueryResponseTooLong {
+                length, max_length, ..
+            } => {
+                assert_eq!(length, 2049);
+                assert_eq!(max_length, 2048);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn region_length_too_big_works() {
         let error = CommunicationError::region_length_too_big(50, 20);
@@ -131,6 +219,35 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn iterator_steps_exceeded_works() {
+        let error = CommunicationError::iterator_steps_exceeded(3, 10);
+        match error {
+            CommunicationError::IteratorStepsExceeded {
+                iterator_id,
+                max_steps,
+                ..
+            } => {
+                assert_eq!(iterator_id, 3);
+                assert_eq!(max_steps, 10);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn vm_allocations_limit_exceeded_works() {
+        let error = CommunicationError::vm_allocations_limit_exceeded(1025, 1024);
+        match error {
+            CommunicationError::VmAllocationsLimitExceeded { size, max_size, .. } => {
+                assert_eq!(size, 1025);
+                assert_eq!(max_size, 1024);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn zero_address() {
         let error = CommunicationError::zero_address();
@@ -1,6 +1,7 @@
 #[cfg(feature = "backtraces")]
 use std::backtrace::Backtrace;
 use std::fmt::{Debug, Display};
+use std::time::Duration;
 use thiserror::Error;
 
 use cosmwasm_crypto::CryptoError;
@@ -42,6 +43,12 @@ pub enum VmError {
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
+    #[error("Compiling Wasm exceeded the timeout of {timeout:?}")]
+    CompileTimeout {
+        timeout: Duration,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Couldn't convert from {} to {}. Input: {}", from_type, to_type, input)]
     ConversionErr {
         from_type: String,
@@ -56,6 +63,20 @@ pub enum VmError {
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
+    #[error(
+        "Error deserializing result of entry point '{entry_point}' ({length} bytes) into type {target_type}: {msg}"
+    )]
+    DeserializeErr {
+        /// the entry point that returned the data
+        entry_point: String,
+        /// the length of the data in bytes
+        length: usize,
+        /// the target type that was attempted
+        target_type: String,
+        msg: String,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Ran out of gas during contract execution")]
     GasDepletion {
         #[cfg(feature = "backtraces")]
@@ -79,6 +100,18 @@ pub enum VmError {
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
+    #[error("Message rejected by the host: {msg}")]
+    MessageRejected {
+        msg: String,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
+    #[error("Contract does not export the entry point '{entry_point}'")]
+    MissingEntryPoint {
+        entry_point: String,
+        #[cfg(feature = "backtraces")]
+        backtrace: Backtrace,
+    },
     #[error("Error parsing into type {target_type}: {msg}")]
     ParseErr {
         /// the target type that was attempted
@@ -125,6 +158,10 @@ pub enum VmError {
     #[error("Error executing Wasm: {}", msg)]
     RuntimeErr {
         msg: String,
+        /// The Wasm frames of the trap, innermost first. Those are not part of the
+        /// message because they depend on the compilation of the contract.
+        #[cfg(feature = "backtraces")]
+        frames: Vec<String>,
         #[cfg(feature = "backtraces")]
         backtrace: Backtrace,
     },
@@ -180,6 +217,14 @@ impl VmError {
         }
     }
 
+    pub(crate) fn compile_timeout(timeout: Duration) -> Self {
+        VmError::CompileTimeout {
+            timeout,
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub(crate) fn conversion_err(
         from_type: impl Into<String>,
         to_type: impl Into<String>,
@@ -202,6 +247,22 @@ impl VmError {
         }
     }
 
+    pub(crate) fn deserialize_err(
+        entry_point: impl Into<String>,
+        length: usize,
+        target: impl Into<String>,
+        msg: impl Display,
+    ) -> Self {
+        VmError::DeserializeErr {
+            entry_point: entry_point.into(),
+            length,
+            target_type: target.into(),
+            msg: msg.to_string(),
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub(crate) fn gas_depletion() -> Self {
         VmError::GasDepletion {
             #[cfg(feature = "backtraces")]
@@ -232,6 +293,22 @@ impl VmError {
         }
     }
 
+    pub(crate) fn message_rejected(msg: impl Into<String>) -> Self {
+        VmError::MessageRejected {
+            msg: msg.into(),
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
+    pub(crate) fn missing_entry_point(entry_point: impl Into<String>) -> Self {
+        VmError::MissingEntryPoint {
+            entry_point: entry_point.into(),
+            #[cfg(feature = "backtraces")]
+            backtrace: Backtrace::capture(),
+        }
+    }
+
     pub(crate) fn parse_err(target: impl Into<String>, msg: impl Display) -> Self {
         VmError::ParseErr {
             target_type: target.into(),
@@ -284,10 +361,15 @@ impl VmError {
     // Creates a runtime error with the given message.
     // This is private since it is only needed when converting wasmer::RuntimeError
     // to VmError.
-    fn runtime_err(msg: impl Into<String>) -> Self {
+    fn runtime_err(
+        msg: impl Into<String>,
+        #[cfg(feature = "backtraces")] frames: Vec<String>,
+    ) -> Self {
         VmError::RuntimeErr {
             msg: msg.into(),
             #[cfg(feature = "backtraces")]
+            frames,
+            #[cfg(feature = "backtraces")]
             backtrace: Backtrace::capture(),
         }
     }
@@ -363,10 +445,30 @@ impl From<wasmer::RuntimeError> for VmError {
             &message,
             original
         );
-        VmError::runtime_err(format!("Wasmer runtime error: {}", &message))
+        VmError::runtime_err(
+            format!("Wasmer runtime error: {}", &message),
+            #[cfg(feature = "backtraces")]
+            original.trace().iter().map(format_frame).collect(),
+        )
     }
 }
 
+/// Formats a Wasm frame like the Display implementation of `wasmer::RuntimeError`
+#[cfg(feature = "backtraces")]
+fn format_frame(frame: &wasmer::FrameInfo) -> String {
+    let function_name = match frame.function_name() {
+        Some(name) => name.to_string(),
+        None => format!("<unnamed function {}>", frame.func_index()),
+    };
+    format!(
+        "{} ({}[{}]:0x{:x})",
+        function_name,
+        frame.module_name(),
+        frame.func_index(),
+        frame.module_offset()
+    )
+}
+
 impl From<wasmer::CompileError> for VmError {
     fn from(original: wasmer::CompileError) -> Self {
         VmError::compile_err(format!("Could not compile: {}", original))
@@ -422,6 +524,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn compile_timeout_works() {
+        let error = VmError::compile_timeout(Duration::from_millis(1500));
+        match error {
+            VmError::CompileTimeout { timeout, .. } => {
+                assert_eq!(timeout, Duration::from_millis(1500))
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn conversion_err_works() {
         let error = VmError::conversion_err("i32", "u32", "-9");
@@ -452,6 +565,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_err_works() {
+        let error = VmError::deserialize_err("query", 42, "Book", "Missing field: title");
+        match error {
+            VmError::DeserializeErr {
+                entry_point,
+                length,
+                target_type,
+                msg,
+                ..
+            } => {
+                assert_eq!(entry_point, "query");
+                assert_eq!(length, 42);
+                assert_eq!(target_type, "Book");
+                assert_eq!(msg, "Missing field: title");
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn gas_depletion_works() {
         let error = VmError::gas_depletion();
@@ -491,6 +624,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn message_rejected_works() {
+        let error = VmError::message_rejected("bank sends are not allowed");
+        match error {
+            VmError::MessageRejected { msg, .. } => assert_eq!(msg, "bank sends are not allowed"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn missing_entry_point_works() {
+        let error = VmError::missing_entry_point("reply");
+        match error {
+            VmError::MissingEntryPoint { entry_point, .. } => assert_eq!(entry_point, "reply"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn parse_err_works() {
         let error = VmError::parse_err("Book", "Missing field: title");
@@ -548,7 +699,11 @@ mod tests {
 
     #[test]
     fn runtime_err_works() {
-        let error = VmError::runtime_err("something went wrong");
+        let error = VmError::runtime_err(
+            "something went wrong",
+            #[cfg(feature = "backtraces")]
+            vec![],
+        );
         match error {
             VmError::RuntimeErr { msg, .. } => assert_eq!(msg, "something went wrong"),
             e => panic!("Unexpected error: {:?}", e),
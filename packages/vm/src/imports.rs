@@ -1,6 +1,7 @@
 //! Import implementations
 
-use std::cmp::max;
+use serde::Serialize;
+use std::cmp::{max, min};
 
 use cosmwasm_crypto::{
     ed25519_batch_verify, ed25519_verify, secp256k1_recover_pubkey, secp256k1_verify,
@@ -19,11 +20,11 @@ use crate::environment::{process_gas_info, Environment};
 use crate::errors::{CommunicationError, VmError, VmResult};
 #[cfg(feature = "iterator")]
 use crate::memory::maybe_read_region;
-use crate::memory::{read_region, write_region};
+use crate::memory::{read_region, write_region, write_region_with};
 use crate::sections::decode_sections;
 #[allow(unused_imports)]
 use crate::sections::encode_sections;
-use crate::serde::to_vec;
+use crate::serde::{serialized_len, to_writer};
 use crate::GasInfo;
 
 /// A kibi (kilo binary)
@@ -79,7 +80,8 @@ pub fn do_db_read<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     key_ptr: u32,
 ) -> VmResult<u32> {
-    let key = read_region(&env.memory(), key_ptr, MAX_LENGTH_DB_KEY)?;
+    let key = read_from_contract(env, key_ptr, MAX_LENGTH_DB_KEY)?;
+    env.record_read(&key);
 
     let (result, gas_info) = env.with_storage_from_context::<_, _>(|store| Ok(store.get(&key)))?;
     process_gas_info::<A, S, Q>(env, gas_info)?;
@@ -89,6 +91,41 @@ pub fn do_db_read<A: BackendApi, S: Storage, Q: Querier>(
         Some(data) => data,
         None => return Ok(0),
     };
+    // Values are limited on the way in but the backend's storage may contain longer ones
+    if out_data.len() > MAX_LENGTH_DB_VALUE {
+        return Err(
+            CommunicationError::db_value_too_long(out_data.len(), MAX_LENGTH_DB_VALUE).into(),
+        );
+    }
+    write_to_contract::<A, S, Q>(env, &out_data)
+}
+
+/// Reads `length` bytes starting at `offset` of a storage entry into Wasm memory.
+/// Only the requested slice is copied into the contract. The gas charged is the one reported
+/// by the backend, which may cover the full value (see [`Storage::get_range`]). The slice is
+/// shorter or empty if the value ends before `offset + length`.
+pub fn do_db_read_range<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+    key_ptr: u32,
+    offset: u32,
+    length: u32,
+) -> VmResult<u32> {
+    let key = read_from_contract(env, key_ptr, MAX_LENGTH_DB_KEY)?;
+    env.record_read(&key);
+
+    let (result, gas_info) = env.with_storage_from_context::<_, _>(|store| {
+        Ok(store.get_range(&key, offset as usize, length as usize))
+    })?;
+    process_gas_info::<A, S, Q>(env, gas_info)?;
+    let out_data = match result? {
+        Some(data) => data,
+        None => return Ok(0),
+    };
+    if out_data.len() > MAX_LENGTH_DB_VALUE {
+        return Err(
+            CommunicationError::db_value_too_long(out_data.len(), MAX_LENGTH_DB_VALUE).into(),
+        );
+    }
     write_to_contract::<A, S, Q>(env, &out_data)
 }
 
@@ -102,8 +139,9 @@ pub fn do_db_write<A: BackendApi, S: Storage, Q: Querier>(
         return Err(VmError::write_access_denied());
     }
 
-    let key = read_region(&env.memory(), key_ptr, MAX_LENGTH_DB_KEY)?;
-    let value = read_region(&env.memory(), value_ptr, MAX_LENGTH_DB_VALUE)?;
+    let key = read_from_contract(env, key_ptr, MAX_LENGTH_DB_KEY)?;
+    let value = read_from_contract(env, value_ptr, MAX_LENGTH_DB_VALUE)?;
+    env.record_write(&key);
 
     let (result, gas_info) =
         env.with_storage_from_context::<_, _>(|store| Ok(store.set(&key, &value)))?;
@@ -121,7 +159,8 @@ pub fn do_db_remove<A: BackendApi, S: Storage, Q: Querier>(
         return Err(VmError::write_access_denied());
     }
 
-    let key = read_region(&env.memory(), key_ptr, MAX_LENGTH_DB_KEY)?;
+    let key = read_from_contract(env, key_ptr, MAX_LENGTH_DB_KEY)?;
+    env.record_write(&key);
 
     let (result, gas_info) =
         env.with_storage_from_context::<_, _>(|store| Ok(store.remove(&key)))?;
@@ -131,11 +170,29 @@ pub fn do_db_remove<A: BackendApi, S: Storage, Q: Querier>(
     Ok(())
 }
 
+/// Returns the block time of the current call in nanoseconds since epoch
+pub fn do_get_block_time<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+) -> VmResult<u64> {
+    let gas_info = GasInfo::with_cost(env.gas_config.block_info_cost);
+    process_gas_info::<A, S, Q>(env, gas_info)?;
+    Ok(env.block_info()?.time.nanos())
+}
+
+/// Returns the block height of the current call
+pub fn do_get_block_height<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+) -> VmResult<u64> {
+    let gas_info = GasInfo::with_cost(env.gas_config.block_info_cost);
+    process_gas_info::<A, S, Q>(env, gas_info)?;
+    Ok(env.block_info()?.height)
+}
+
 pub fn do_addr_validate<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     source_ptr: u32,
 ) -> VmResult<u32> {
-    let source_data = read_region(&env.memory(), source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
+    let source_data = read_from_contract(env, source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
     if source_data.is_empty() {
         return write_to_contract::<A, S, Q>(env, b"Input is empty");
     }
@@ -145,31 +202,13 @@ pub fn do_addr_validate<A: BackendApi, S: Storage, Q: Querier>(
         Err(_) => return write_to_contract::<A, S, Q>(env, b"Input is not valid UTF-8"),
     };
 
-    let (result, gas_info) = env.api.canonical_address(&source_string);
-    process_gas_info::<A, S, Q>(env, gas_info)?;
-    let canonical = match result {
-        Ok(data) => data,
-        Err(BackendError::UserErr { msg, .. }) => {
-            return write_to_contract::<A, S, Q>(env, msg.as_bytes())
-        }
-        Err(err) => return Err(VmError::from(err)),
-    };
-
-    let (result, gas_info) = env.api.human_address(&canonical);
+    let (result, gas_info) = env.api.addr_validate(&source_string);
     process_gas_info::<A, S, Q>(env, gas_info)?;
-    let normalized = match result {
-        Ok(addr) => addr,
-        Err(BackendError::UserErr { msg, .. }) => {
-            return write_to_contract::<A, S, Q>(env, msg.as_bytes())
-        }
-        Err(err) => return Err(VmError::from(err)),
-    };
-
-    if normalized != source_string {
-        return write_to_contract::<A, S, Q>(env, b"Address is not normalized");
+    match result {
+        Ok(()) => Ok(0),
+        Err(BackendError::UserErr { msg, .. }) => write_to_contract::<A, S, Q>(env, msg.as_bytes()),
+        Err(err) => Err(VmError::from(err)),
     }
-
-    Ok(0)
 }
 
 pub fn do_addr_canonicalize<A: BackendApi, S: Storage, Q: Querier>(
@@ -177,7 +216,8 @@ pub fn do_addr_canonicalize<A: BackendApi, S: Storage, Q: Querier>(
     source_ptr: u32,
     destination_ptr: u32,
 ) -> VmResult<u32> {
-    let source_data = read_region(&env.memory(), source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
+    let source_data = read_from_contract(env, source_ptr, MAX_LENGTH_HUMAN_ADDRESS)?;
+    process_gas_info::<A, S, Q>(env, addr_conversion_gas(env, source_data.len()))?;
     if source_data.is_empty() {
         return write_to_contract::<A, S, Q>(env, b"Input is empty");
     }
@@ -206,7 +246,8 @@ pub fn do_addr_humanize<A: BackendApi, S: Storage, Q: Querier>(
     source_ptr: u32,
     destination_ptr: u32,
 ) -> VmResult<u32> {
-    let canonical = read_region(&env.memory(), source_ptr, MAX_LENGTH_CANONICAL_ADDRESS)?;
+    let canonical = read_from_contract(env, source_ptr, MAX_LENGTH_CANONICAL_ADDRESS)?;
+    process_gas_info::<A, S, Q>(env, addr_conversion_gas(env, canonical.len()))?;
 
     let (result, gas_info) = env.api.human_address(&canonical);
     process_gas_info::<A, S, Q>(env, gas_info)?;
@@ -222,15 +263,27 @@ pub fn do_addr_humanize<A: BackendApi, S: Storage, Q: Querier>(
     }
 }
 
+/// Gas for the VM side work of converting an address of the given length
+fn addr_conversion_gas<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+    input_len: usize,
+) -> GasInfo {
+    GasInfo::with_cost(
+        env.gas_config
+            .addr_conversion_cost
+            .saturating_mul(input_len as u64),
+    )
+}
+
 pub fn do_secp256k1_verify<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     hash_ptr: u32,
     signature_ptr: u32,
     pubkey_ptr: u32,
 ) -> VmResult<u32> {
-    let hash = read_region(&env.memory(), hash_ptr, MESSAGE_HASH_MAX_LEN)?;
-    let signature = read_region(&env.memory(), signature_ptr, ECDSA_SIGNATURE_LEN)?;
-    let pubkey = read_region(&env.memory(), pubkey_ptr, ECDSA_PUBKEY_MAX_LEN)?;
+    let hash = read_from_contract(env, hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+    let signature = read_from_contract(env, signature_ptr, ECDSA_SIGNATURE_LEN)?;
+    let pubkey = read_from_contract(env, pubkey_ptr, ECDSA_PUBKEY_MAX_LEN)?;
 
     let gas_info = GasInfo::with_cost(env.gas_config.secp256k1_verify_cost);
     process_gas_info::<A, S, Q>(env, gas_info)?;
@@ -256,8 +309,8 @@ pub fn do_secp256k1_recover_pubkey<A: BackendApi, S: Storage, Q: Querier>(
     signature_ptr: u32,
     recover_param: u32,
 ) -> VmResult<u64> {
-    let hash = read_region(&env.memory(), hash_ptr, MESSAGE_HASH_MAX_LEN)?;
-    let signature = read_region(&env.memory(), signature_ptr, ECDSA_SIGNATURE_LEN)?;
+    let hash = read_from_contract(env, hash_ptr, MESSAGE_HASH_MAX_LEN)?;
+    let signature = read_from_contract(env, signature_ptr, ECDSA_SIGNATURE_LEN)?;
     let recover_param: u8 = match recover_param.try_into() {
         Ok(rp) => rp,
         Err(_) => return Ok((CryptoError::invalid_recovery_param().code() as u64) << 32),
@@ -290,9 +343,9 @@ pub fn do_ed25519_verify<A: BackendApi, S: Storage, Q: Querier>(
     signature_ptr: u32,
     pubkey_ptr: u32,
 ) -> VmResult<u32> {
-    let message = read_region(&env.memory(), message_ptr, MAX_LENGTH_ED25519_MESSAGE)?;
-    let signature = read_region(&env.memory(), signature_ptr, MAX_LENGTH_ED25519_SIGNATURE)?;
-    let pubkey = read_region(&env.memory(), pubkey_ptr, EDDSA_PUBKEY_LEN)?;
+    let message = read_from_contract(env, message_ptr, MAX_LENGTH_ED25519_MESSAGE)?;
+    let signature = read_from_contract(env, signature_ptr, MAX_LENGTH_ED25519_SIGNATURE)?;
+    let pubkey = read_from_contract(env, pubkey_ptr, EDDSA_PUBKEY_LEN)?;
 
     let gas_info = GasInfo::with_cost(env.gas_config.ed25519_verify_cost);
     process_gas_info::<A, S, Q>(env, gas_info)?;
@@ -318,18 +371,18 @@ pub fn do_ed25519_batch_verify<A: BackendApi, S: Storage, Q: Querier>(
     signatures_ptr: u32,
     public_keys_ptr: u32,
 ) -> VmResult<u32> {
-    let messages = read_region(
-        &env.memory(),
+    let messages = read_from_contract(
+        env,
         messages_ptr,
         (MAX_LENGTH_ED25519_MESSAGE + 4) * MAX_COUNT_ED25519_BATCH,
     )?;
-    let signatures = read_region(
-        &env.memory(),
+    let signatures = read_from_contract(
+        env,
         signatures_ptr,
         (MAX_LENGTH_ED25519_SIGNATURE + 4) * MAX_COUNT_ED25519_BATCH,
     )?;
-    let public_keys = read_region(
-        &env.memory(),
+    let public_keys = read_from_contract(
+        env,
         public_keys_ptr,
         (EDDSA_PUBKEY_LEN + 4) * MAX_COUNT_ED25519_BATCH,
     )?;
@@ -338,6 +391,18 @@ pub fn do_ed25519_batch_verify<A: BackendApi, S: Storage, Q: Querier>(
     let signatures = decode_sections(&signatures);
     let public_keys = decode_sections(&public_keys);
 
+    let batch_size = max(messages.len(), max(signatures.len(), public_keys.len()));
+    let max_batch_size = env
+        .max_ed25519_batch_size
+        .map_or(MAX_COUNT_ED25519_BATCH, |limit| {
+            min(limit, MAX_COUNT_ED25519_BATCH)
+        });
+    if batch_size > max_batch_size {
+        return Err(CommunicationError::batch_too_large(batch_size, max_batch_size).into());
+    }
+
+    // Gas is charged per signature but at least as much as a single verification.
+    // An empty batch is trivially valid.
     let gas_cost = if public_keys.len() == 1 {
         env.gas_config.ed25519_batch_verify_one_pubkey_cost
     } else {
@@ -365,8 +430,8 @@ pub fn do_sha1_calculate<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     hash_inputs_ptr: u32,
 ) -> VmResult<u64> {
-    let hash_inputs = read_region(
-        &env.memory(),
+    let hash_inputs = read_from_contract(
+        env,
         hash_inputs_ptr,
         (MAX_LENGTH_SHA1_MESSAGE + 4) * MAX_COUNT_SHA1_INPUT,
     )?;
@@ -408,9 +473,10 @@ pub fn do_debug<A: BackendApi, S: Storage, Q: Querier>(
     message_ptr: u32,
 ) -> VmResult<()> {
     if env.print_debug {
-        let message_data = read_region(&env.memory(), message_ptr, MAX_LENGTH_DEBUG)?;
+        let message_data = read_from_contract(env, message_ptr, MAX_LENGTH_DEBUG)?;
         let msg = String::from_utf8_lossy(&message_data);
         println!("{}", msg);
+        env.set_last_debug_message(msg.into_owned());
     }
     Ok(())
 }
@@ -420,23 +486,67 @@ pub fn do_abort<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     message_ptr: u32,
 ) -> VmResult<()> {
-    let message_data = read_region(&env.memory(), message_ptr, MAX_LENGTH_ABORT)?;
+    let message_data = read_from_contract(env, message_ptr, MAX_LENGTH_ABORT)?;
     let msg = String::from_utf8_lossy(&message_data);
     Err(VmError::aborted(msg))
 }
 
+/// Reads the Region at the given memory location in the contract. The given `max_length`
+/// is capped by the VM's `max_region_read_len` if set.
+fn read_from_contract<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+    ptr: u32,
+    max_length: usize,
+) -> VmResult<Vec<u8>> {
+    read_region(&env.memory(), ptr, env.region_read_limit(max_length))
+}
+
 /// Creates a Region in the contract, writes the given data to it and returns the memory location
 fn write_to_contract<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     input: &[u8],
 ) -> VmResult<u32> {
-    let out_size = to_u32(input.len())?;
+    let target_ptr = allocate_in_contract(env, input.len())?;
+    write_region(&env.memory(), target_ptr, input)?;
+    Ok(target_ptr)
+}
+
+/// Creates a Region in the contract and serializes the given value directly into it.
+/// Returns the memory location of the Region.
+///
+/// Compared to serializing into a buffer and using [`write_to_contract`], this avoids
+/// holding a copy of potentially large data on the host side at the cost of serializing twice.
+/// Values longer than `max_length` are rejected before anything is allocated in the contract.
+fn write_serialized_to_contract<A: BackendApi, S: Storage, Q: Querier, T: Serialize>(
+    env: &Environment<A, S, Q>,
+    value: &T,
+    max_length: Option<usize>,
+) -> VmResult<u32> {
+    let length = serialized_len(value)?;
+    if let Some(max_length) = max_length {
+        if length > max_length {
+            return Err(CommunicationError::query_response_too_long(length, max_length).into());
+        }
+    }
+    let target_ptr = allocate_in_contract(env, length)?;
+    write_region_with(&env.memory(), target_ptr, length, |writer| {
+        to_writer(writer, value)
+    })?;
+    Ok(target_ptr)
+}
+
+/// Allocates a Region of the given size in the contract and returns its memory location
+fn allocate_in_contract<A: BackendApi, S: Storage, Q: Querier>(
+    env: &Environment<A, S, Q>,
+    size: usize,
+) -> VmResult<u32> {
+    let out_size = to_u32(size)?;
+    env.record_vm_allocation(size)?;
     let result = env.call_function1("allocate", &[out_size.into()])?;
     let target_ptr = ref_to_u32(&result)?;
     if target_ptr == 0 {
         return Err(CommunicationError::zero_address().into());
     }
-    write_region(&env.memory(), target_ptr, input)?;
     Ok(target_ptr)
 }
 
@@ -444,15 +554,20 @@ pub fn do_query_chain<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     request_ptr: u32,
 ) -> VmResult<u32> {
-    let request = read_region(&env.memory(), request_ptr, MAX_LENGTH_QUERY_CHAIN_REQUEST)?;
+    let request = read_from_contract(env, request_ptr, MAX_LENGTH_QUERY_CHAIN_REQUEST)?;
 
     let gas_remaining = env.get_gas_left();
     let (result, gas_info) = env.with_querier_from_context::<_, _>(|querier| {
         Ok(querier.query_raw(&request, gas_remaining))
     })?;
+    let gas_info = if env.free_queries {
+        GasInfo::free()
+    } else {
+        gas_info
+    };
     process_gas_info::<A, S, Q>(env, gas_info)?;
-    let serialized = to_vec(&result?)?;
-    write_to_contract::<A, S, Q>(env, &serialized)
+    let result = result?;
+    write_serialized_to_contract::<A, S, Q, _>(env, &result, env.max_query_response_len)
 }
 
 #[cfg(feature = "iterator")]
@@ -462,8 +577,16 @@ pub fn do_db_scan<A: BackendApi, S: Storage, Q: Querier>(
     end_ptr: u32,
     order: i32,
 ) -> VmResult<u32> {
-    let start = maybe_read_region(&env.memory(), start_ptr, MAX_LENGTH_DB_KEY)?;
-    let end = maybe_read_region(&env.memory(), end_ptr, MAX_LENGTH_DB_KEY)?;
+    let start = maybe_read_region(
+        &env.memory(),
+        start_ptr,
+        env.region_read_limit(MAX_LENGTH_DB_KEY),
+    )?;
+    let end = maybe_read_region(
+        &env.memory(),
+        end_ptr,
+        env.region_read_limit(MAX_LENGTH_DB_KEY),
+    )?;
     let order: Order = order
         .try_into()
         .map_err(|_| CommunicationError::invalid_order(order))?;
@@ -473,6 +596,7 @@ pub fn do_db_scan<A: BackendApi, S: Storage, Q: Querier>(
     })?;
     process_gas_info::<A, S, Q>(env, gas_info)?;
     let iterator_id = result?;
+    env.record_new_iterator(iterator_id)?;
     Ok(iterator_id)
 }
 
@@ -481,12 +605,23 @@ pub fn do_db_next<A: BackendApi, S: Storage, Q: Querier>(
     env: &Environment<A, S, Q>,
     iterator_id: u32,
 ) -> VmResult<u32> {
-    let (result, gas_info) =
-        env.with_storage_from_context::<_, _>(|store| Ok(store.next(iterator_id)))?;
-    process_gas_info::<A, S, Q>(env, gas_info)?;
+    env.record_iterator_step(iterator_id)?;
+    // Exhausted iterators stay exhausted, no matter what the backend does with the id
+    let item = if env.is_iterator_exhausted(iterator_id) {
+        None
+    } else {
+        let (result, gas_info) =
+            env.with_storage_from_context::<_, _>(|store| Ok(store.next(iterator_id)))?;
+        process_gas_info::<A, S, Q>(env, gas_info)?;
+        let item = result?;
+        if item.is_none() {
+            env.mark_iterator_exhausted(iterator_id);
+        }
+        item
+    };
 
     // Empty key will later be treated as _no more element_.
-    let (key, value) = result?.unwrap_or_else(|| (Vec::<u8>::new(), Vec::<u8>::new()));
+    let (key, value) = item.unwrap_or_else(|| (Vec::<u8>::new(), Vec::<u8>::new()));
 
     let out_data = encode_sections(&[key, value])?;
     write_to_contract::<A, S, Q>(env, &out_data)
@@ -516,8 +651,8 @@ fn to_low_half(data: u32) -> u64 {
 mod tests {
     use super::*;
     use cosmwasm_std::{
-        coins, from_binary, AllBalanceResponse, BankQuery, Binary, Empty, QueryRequest,
-        SystemError, SystemResult, WasmQuery,
+        coins, from_binary, AllBalanceResponse, BankQuery, Binary, ContractResult, Empty,
+        QueryRequest, SystemError, SystemResult, WasmQuery,
     };
     use hex_literal::hex;
     use std::ptr::NonNull;
@@ -525,7 +660,9 @@ mod tests {
 
     use crate::backend::{BackendError, Storage};
     use crate::size::Size;
-    use crate::testing::{MockApi, MockQuerier, MockStorage};
+    use crate::testing::{
+        mock_backend_with_gas_costs, mock_env, MockApi, MockQuerier, MockStorage,
+    };
     use crate::wasm_backend::compile;
 
     static CONTRACT: &[u8] = include_bytes!("../testdata/hackatom.wasm");
@@ -656,6 +793,172 @@ mod tests {
         assert_eq!(result.unwrap(), 0);
     }
 
+    #[test]
+    fn do_db_read_range_works() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        let key_ptr = write_data(&env, KEY1);
+
+        // prefix
+        let value_ptr = do_db_read_range(&env, key_ptr, 0, 3).unwrap();
+        assert_eq!(force_read(&env, value_ptr), b"ins");
+
+        // middle slice
+        let value_ptr = do_db_read_range(&env, key_ptr, 2, 3).unwrap();
+        assert_eq!(force_read(&env, value_ptr), b"sec");
+
+        // slice reaching beyond the end
+        let value_ptr = do_db_read_range(&env, key_ptr, 4, 100).unwrap();
+        assert_eq!(force_read(&env, value_ptr), b"ct");
+        let value_ptr = do_db_read_range(&env, key_ptr, 0, u32::MAX).unwrap();
+        assert_eq!(force_read(&env, value_ptr), VALUE1);
+
+        // out-of-range offset
+        let value_ptr = do_db_read_range(&env, key_ptr, 6, 1).unwrap();
+        assert_eq!(force_read(&env, value_ptr), b"");
+        let value_ptr = do_db_read_range(&env, key_ptr, u32::MAX, u32::MAX).unwrap();
+        assert_eq!(force_read(&env, value_ptr), b"");
+
+        // non-existent key
+        let key_ptr = write_data(&env, b"I do not exist in storage");
+        assert_eq!(do_db_read_range(&env, key_ptr, 0, 3).unwrap(), 0);
+    }
+
+    #[test]
+    fn do_db_read_range_charges_for_the_slice_only() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        let mut storage = MockStorage::new();
+        storage.set(KEY1, &[0x42; 100]).0.unwrap();
+        storage.set(KEY2, &vec![0x42; 100_000]).0.unwrap();
+        env.move_in(storage, MockQuerier::new(&[]));
+
+        fn externally_used(env: &Environment<MockApi, MockStorage, MockQuerier>) -> u64 {
+            env.with_gas_state(|gas_state| gas_state.externally_used_gas)
+        }
+        let read_range_cost = |key: &[u8], offset: u32, length: u32| {
+            let key_ptr = write_data(&env, key);
+            let before = externally_used(&env);
+            do_db_read_range(&env, key_ptr, offset, length).unwrap();
+            externally_used(&env) - before
+        };
+
+        // the mock storage charges the key length plus the slice length
+        assert_eq!(read_range_cost(KEY1, 0, 3), (KEY1.len() + 3) as u64);
+        assert_eq!(read_range_cost(KEY1, 98, 3), (KEY1.len() + 2) as u64);
+        assert_eq!(read_range_cost(KEY1, 200, 3), KEY1.len() as u64);
+        // the size of the full value does not matter
+        assert_eq!(read_range_cost(KEY2, 0, 3), (KEY2.len() + 3) as u64);
+        assert_eq!(read_range_cost(KEY2, 50_000, 3), (KEY2.len() + 3) as u64);
+    }
+
+    #[test]
+    fn do_db_read_fails_for_oversized_value() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        let mut storage = MockStorage::new();
+        storage
+            .set(KEY1, &vec![0x42; MAX_LENGTH_DB_VALUE])
+            .0
+            .expect("error setting");
+        storage
+            .set(KEY2, &vec![0x42; MAX_LENGTH_DB_VALUE + 1])
+            .0
+            .expect("error setting");
+        env.move_in(storage, MockQuerier::new(&[]));
+
+        // the max length is fine
+        let key_ptr = write_data(&env, KEY1);
+        let value_ptr = do_db_read(&env, key_ptr).unwrap();
+        let value = read_region(&env.memory(), value_ptr, MAX_LENGTH_DB_VALUE).unwrap();
+        assert_eq!(value.len(), MAX_LENGTH_DB_VALUE);
+
+        let key_ptr = write_data(&env, KEY2);
+        match do_db_read(&env, key_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::DbValueTooLong { length, max_length },
+                ..
+            } => {
+                assert_eq!(length, MAX_LENGTH_DB_VALUE + 1);
+                assert_eq!(max_length, MAX_LENGTH_DB_VALUE);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_get_block_time_and_height_work() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+        let block = mock_env().block;
+        env.set_serialized_env(&cosmwasm_std::to_vec(&mock_env()).unwrap());
+
+        let gas_before = env.get_gas_left();
+        assert_eq!(do_get_block_time(&env).unwrap(), block.time.nanos());
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            env.gas_config.block_info_cost
+        );
+        assert_eq!(do_get_block_height(&env).unwrap(), block.height);
+
+        // a new env replaces the cached block
+        let mut other_env = mock_env();
+        other_env.block.height += 1;
+        other_env.block.time = other_env.block.time.plus_seconds(5);
+        env.set_serialized_env(&cosmwasm_std::to_vec(&other_env).unwrap());
+        assert_eq!(do_get_block_height(&env).unwrap(), block.height + 1);
+        assert_eq!(
+            do_get_block_time(&env).unwrap(),
+            block.time.plus_seconds(5).nanos()
+        );
+    }
+
+    #[test]
+    fn do_get_block_height_fails_without_env() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+        leave_default_data(&env);
+
+        match do_get_block_height(&env).unwrap_err() {
+            VmError::GenericErr { msg, .. } => assert_eq!(msg, "No env set for the current call"),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn do_db_read_respects_max_vm_allocations_bytes() {
+        let api = MockApi::default();
+        let (mut env, _instance) = make_instance(api);
+        env.max_vm_allocations_bytes = Some(3000);
+        let value = vec![0x42; 1000];
+        let mut storage = MockStorage::new();
+        storage.set(KEY1, &value).0.expect("error setting");
+        env.move_in(storage, MockQuerier::new(&[]));
+
+        let key_ptr = write_data(&env, KEY1);
+        for _ in 0..3 {
+            let value_ptr = do_db_read(&env, key_ptr).unwrap();
+            assert_eq!(force_read(&env, value_ptr), value);
+        }
+        match do_db_read(&env, key_ptr).unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::VmAllocationsLimitExceeded { size, max_size },
+                ..
+            } => {
+                assert_eq!(size, 4000);
+                assert_eq!(max_size, 3000);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // the counter starts over in the next call
+        env.reset_vm_allocated_bytes();
+        do_db_read(&env, key_ptr).unwrap();
+    }
+
     #[test]
     fn do_db_read_fails_for_large_key() {
         let api = MockApi::default();
@@ -696,6 +999,23 @@ mod tests {
         assert_eq!(val, Some(b"new value".to_vec()));
     }
 
+    #[test]
+    fn do_db_write_charges_backend_gas() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        let key_ptr = write_data(&env, b"new storage key");
+        let value_ptr = write_data(&env, b"new value");
+
+        let backend = mock_backend_with_gas_costs(7, 1234);
+        env.move_in(backend.storage, backend.querier);
+
+        let externally_used_before = env.with_gas_state(|gas_state| gas_state.externally_used_gas);
+        do_db_write(&env, key_ptr, value_ptr).unwrap();
+        let externally_used_after = env.with_gas_state(|gas_state| gas_state.externally_used_gas);
+        assert_eq!(externally_used_after - externally_used_before, 1234);
+    }
+
     #[test]
     fn do_db_write_can_override() {
         let api = MockApi::default();
@@ -949,6 +1269,26 @@ mod tests {
         assert_eq!(err, "Address is not normalized");
     }
 
+    #[test]
+    fn do_addr_validate_charges_gas_once() {
+        let api = MockApi::new_with_gas_cost(100, 1000);
+        let (env, _instance) = make_instance(api);
+
+        let source_ptr1 = write_data(&env, b"foo");
+        let source_ptr2 = write_data(&env, b"fooBar"); // not normalized
+
+        let gas_before = env.get_gas_left();
+        let res = do_addr_validate(&env, source_ptr1).unwrap();
+        assert_eq!(res, 0);
+        assert_eq!(gas_before - env.get_gas_left(), 1100);
+
+        // invalid addresses are reported back to the contract
+        let res = do_addr_validate(&env, source_ptr2).unwrap();
+        assert_ne!(res, 0);
+        let err = String::from_utf8(force_read(&env, res)).unwrap();
+        assert_eq!(err, "Address is not normalized");
+    }
+
     #[test]
     fn do_addr_validate_fails_for_broken_backend() {
         let api = MockApi::new_failing("Temporarily unavailable");
@@ -1011,6 +1351,35 @@ mod tests {
         assert_eq!(data.len(), api.canonical_length());
     }
 
+    #[test]
+    fn do_addr_canonicalize_charges_gas_per_input_byte() {
+        let api = MockApi::new_with_gas_cost(100, 1000);
+        let (env, mut instance) = make_instance(api);
+        let api = MockApi::default();
+
+        let source_ptr1 = write_data(&env, b"foo");
+        let source_ptr2 = write_data(&env, b"foobarbaz");
+        let dest_ptr = create_empty(&mut instance, api.canonical_length() as u32);
+
+        leave_default_data(&env);
+
+        let gas_before = env.get_gas_left();
+        let res = do_addr_canonicalize(&env, source_ptr1, dest_ptr).unwrap();
+        assert_eq!(res, 0);
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            100 + 3 * env.gas_config.addr_conversion_cost
+        );
+
+        let gas_before = env.get_gas_left();
+        let res = do_addr_canonicalize(&env, source_ptr2, dest_ptr).unwrap();
+        assert_eq!(res, 0);
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            100 + 9 * env.gas_config.addr_conversion_cost
+        );
+    }
+
     #[test]
     fn do_addr_canonicalize_reports_invalid_input_back_to_contract() {
         let api = MockApi::default();
@@ -1114,15 +1483,36 @@ mod tests {
         let (env, mut instance) = make_instance(api);
         let api = MockApi::default();
 
-        let source_data = vec![0x22; api.canonical_length()];
+        let source_data = api.canonical_address("foo").0.unwrap();
+        let source_ptr = write_data(&env, &source_data);
+        let dest_ptr = create_empty(&mut instance, 70);
+
+        leave_default_data(&env);
+
+        let error_ptr = do_addr_humanize(&env, source_ptr, dest_ptr).unwrap();
+        assert_eq!(error_ptr, 0);
+        assert_eq!(force_read(&env, dest_ptr), b"foo");
+    }
+
+    #[test]
+    fn do_addr_humanize_charges_gas_per_input_byte() {
+        let api = MockApi::new_with_gas_cost(100, 1000);
+        let (env, mut instance) = make_instance(api);
+        let api = MockApi::default();
+
+        let source_data = api.canonical_address("foo").0.unwrap();
         let source_ptr = write_data(&env, &source_data);
         let dest_ptr = create_empty(&mut instance, 70);
 
         leave_default_data(&env);
 
+        let gas_before = env.get_gas_left();
         let error_ptr = do_addr_humanize(&env, source_ptr, dest_ptr).unwrap();
         assert_eq!(error_ptr, 0);
-        assert_eq!(force_read(&env, dest_ptr), source_data);
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            1000 + source_data.len() as u64 * env.gas_config.addr_conversion_cost
+        );
     }
 
     #[test]
@@ -1193,7 +1583,7 @@ mod tests {
         let (env, mut instance) = make_instance(api);
         let api = MockApi::default();
 
-        let source_data = vec![0x22; api.canonical_length()];
+        let source_data = api.canonical_address("foobar").0.unwrap();
         let source_ptr = write_data(&env, &source_data);
         let dest_ptr = create_empty(&mut instance, 2);
 
@@ -1206,7 +1596,7 @@ mod tests {
                 ..
             } => {
                 assert_eq!(size, 2);
-                assert_eq!(required, api.canonical_length());
+                assert_eq!(required, 6);
             }
             err => panic!("Incorrect error returned: {:?}", err),
         }
@@ -1724,6 +2114,101 @@ mod tests {
         )
     }
 
+    #[test]
+    fn do_ed25519_batch_verify_works_for_empty_batch() {
+        let api = MockApi::default();
+        let (env, mut _instance) = make_instance(api);
+
+        let msgs_ptr = write_data(&env, &encode_sections(&[]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&[]).unwrap());
+        let pubkeys_ptr = write_data(&env, &encode_sections(&[]).unwrap());
+
+        let gas_before = env.get_gas_left();
+        assert_eq!(
+            do_ed25519_batch_verify(&env, msgs_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            0
+        );
+        // charged like a single verification
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            env.gas_config.ed25519_verify_cost
+        );
+    }
+
+    #[test]
+    fn do_ed25519_batch_verify_respects_max_batch_size() {
+        let api = MockApi::default();
+        let (mut env, mut _instance) = make_instance(api);
+        env.max_ed25519_batch_size = Some(5);
+
+        let msg = hex::decode(EDDSA_MSG_HEX).unwrap();
+        let sig = hex::decode(EDDSA_SIG_HEX).unwrap();
+        let pubkeys = encode_sections(&[hex::decode(EDDSA_PUBKEY_HEX).unwrap()]).unwrap();
+
+        // at limit
+        let msgs_ptr = write_data(&env, &encode_sections(&vec![msg.clone(); 5]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&vec![sig.clone(); 5]).unwrap());
+        let pubkeys_ptr = write_data(&env, &pubkeys);
+        let gas_before = env.get_gas_left();
+        assert_eq!(
+            do_ed25519_batch_verify(&env, msgs_ptr, sigs_ptr, pubkeys_ptr).unwrap(),
+            0
+        );
+        // charged per signature
+        assert_eq!(
+            gas_before - env.get_gas_left(),
+            5 * env.gas_config.ed25519_batch_verify_one_pubkey_cost
+        );
+
+        // over limit
+        let msgs_ptr = write_data(&env, &encode_sections(&vec![msg; 6]).unwrap());
+        let sigs_ptr = write_data(&env, &encode_sections(&vec![sig; 6]).unwrap());
+        let pubkeys_ptr = write_data(&env, &pubkeys);
+        let gas_before = env.get_gas_left();
+        let result = do_ed25519_batch_verify(&env, msgs_ptr, sigs_ptr, pubkeys_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::BatchTooLarge { size, max_size },
+                ..
+            } => {
+                assert_eq!(size, 6);
+                assert_eq!(max_size, 5);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+        // no verification gas charged
+        assert_eq!(gas_before, env.get_gas_left());
+    }
+
+    #[test]
+    fn do_ed25519_batch_verify_limit_cannot_exceed_built_in_limit() {
+        let api = MockApi::default();
+        let (mut env, mut _instance) = make_instance(api);
+        env.max_ed25519_batch_size = Some(MAX_COUNT_ED25519_BATCH + 10);
+
+        let msgs = vec![vec![]; MAX_COUNT_ED25519_BATCH + 1];
+        let msgs_ptr = write_data(&env, &encode_sections(&msgs).unwrap());
+        let sigs_ptr = write_data(
+            &env,
+            &encode_sections(&[hex::decode(EDDSA_SIG_HEX).unwrap()]).unwrap(),
+        );
+        let pubkeys_ptr = write_data(
+            &env,
+            &encode_sections(&[hex::decode(EDDSA_PUBKEY_HEX).unwrap()]).unwrap(),
+        );
+        let result = do_ed25519_batch_verify(&env, msgs_ptr, sigs_ptr, pubkeys_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source: CommunicationError::BatchTooLarge { size, max_size },
+                ..
+            } => {
+                assert_eq!(size, MAX_COUNT_ED25519_BATCH + 1);
+                assert_eq!(max_size, MAX_COUNT_ED25519_BATCH);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
     #[test]
     fn do_query_chain_works() {
         let api = MockApi::default();
@@ -1748,6 +2233,71 @@ mod tests {
         assert_eq!(parsed_again.amount, coins(INIT_AMOUNT, INIT_DENOM));
     }
 
+    #[test]
+    fn do_query_chain_works_for_large_responses() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        // 300 KiB of data with all byte values, which are base64 encoded in the response
+        let data: Vec<u8> = (0..300 * 1024).map(|i| (i % 251) as u8).collect();
+        let expected = data.clone();
+
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: String::from("big"),
+            msg: Binary::from(b"{}" as &[u8]),
+        });
+        let request_data = cosmwasm_std::to_vec(&request).unwrap();
+        let request_ptr = write_data(&env, &request_data);
+
+        let mut querier: MockQuerier<Empty> = MockQuerier::new(&[]);
+        querier.update_wasm(move |_| SystemResult::Ok(ContractResult::Ok(Binary(data.clone()))));
+        env.move_in(MockStorage::new(), querier);
+
+        let response_ptr = do_query_chain(&env, request_ptr).unwrap();
+        let response = read_region(&env.memory(), response_ptr, 1024 * 1024).unwrap();
+
+        let query_result: cosmwasm_std::QuerierResult =
+            cosmwasm_std::from_slice(&response).unwrap();
+        assert_eq!(query_result.unwrap().unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn do_query_chain_respects_max_query_response_len() {
+        let api = MockApi::default();
+        let (mut env, _instance) = make_instance(api);
+
+        let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: String::from("big"),
+            msg: Binary::from(b"{}" as &[u8]),
+        });
+        let request_data = cosmwasm_std::to_vec(&request).unwrap();
+        let request_ptr = write_data(&env, &request_data);
+
+        // 3000 bytes are 4000 bytes in base64 plus some JSON around it
+        let mut querier: MockQuerier<Empty> = MockQuerier::new(&[]);
+        querier.update_wasm(|_| SystemResult::Ok(ContractResult::Ok(Binary(vec![0x42; 3000]))));
+        env.move_in(MockStorage::new(), querier);
+
+        env.max_query_response_len = Some(4100);
+        do_query_chain(&env, request_ptr).unwrap();
+
+        env.max_query_response_len = Some(4000);
+        let result = do_query_chain(&env, request_ptr);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source:
+                    CommunicationError::QueryResponseTooLong {
+                        length, max_length, ..
+                    },
+                ..
+            } => {
+                assert!(length > 4000);
+                assert_eq!(max_length, 4000);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
     #[test]
     fn do_query_chain_fails_for_broken_request() {
         let api = MockApi::default();
@@ -1800,6 +2350,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn do_query_chain_free_queries_works() {
+        let api = MockApi::default();
+        let (mut env, _instance) = make_instance(api);
+
+        let request: QueryRequest<Empty> = QueryRequest::Bank(BankQuery::AllBalances {
+            address: INIT_ADDR.to_string(),
+        });
+        let request_data = cosmwasm_std::to_vec(&request).unwrap();
+        let request_ptr = write_data(&env, &request_data);
+
+        leave_default_data(&env);
+
+        fn externally_used(env: &Environment<MockApi, MockStorage, MockQuerier>) -> u64 {
+            env.with_gas_state(|gas_state| gas_state.externally_used_gas)
+        }
+
+        let gas_before = env.get_gas_left();
+        do_query_chain(&env, request_ptr).unwrap();
+        let charged = gas_before - env.get_gas_left();
+        assert!(externally_used(&env) > 0);
+
+        // the querier's gas is not charged, only the allocation in the contract
+        env.free_queries = true;
+        let externally_used_before = externally_used(&env);
+        let gas_before = env.get_gas_left();
+        let response_ptr = do_query_chain(&env, request_ptr).unwrap();
+        let charged_free = gas_before - env.get_gas_left();
+        assert_eq!(externally_used(&env), externally_used_before);
+        assert_eq!(charged - charged_free, externally_used_before);
+
+        // the response is the same
+        let response = force_read(&env, response_ptr);
+        let query_result: cosmwasm_std::QuerierResult =
+            cosmwasm_std::from_slice(&response).unwrap();
+        let parsed: AllBalanceResponse = from_binary(&query_result.unwrap().unwrap()).unwrap();
+        assert_eq!(parsed.amount, coins(INIT_AMOUNT, INIT_DENOM));
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn do_db_scan_unbound_works() {
@@ -1970,6 +2559,96 @@ mod tests {
         // API makes no guarantees for value_ptr in this case
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn do_db_next_respects_max_iterator_steps() {
+        let api = MockApi::default();
+        let (mut env, _instance) = make_instance(api);
+        env.max_iterator_steps = Some(2);
+
+        leave_default_data(&env);
+
+        let id = do_db_scan(&env, 0, 0, Order::Ascending.into()).unwrap();
+        do_db_next(&env, id).unwrap();
+        do_db_next(&env, id).unwrap();
+        match do_db_next(&env, id).unwrap_err() {
+            VmError::CommunicationErr {
+                source:
+                    CommunicationError::IteratorStepsExceeded {
+                        iterator_id,
+                        max_steps,
+                    },
+                ..
+            } => {
+                assert_eq!(iterator_id, id);
+                assert_eq!(max_steps, 2);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // steps are counted per iterator
+        let id2 = do_db_scan(&env, 0, 0, Order::Descending.into()).unwrap();
+        assert_ne!(id2, id);
+        let kv_region_ptr = do_db_next(&env, id2).unwrap();
+        assert_eq!(
+            force_read(&env, kv_region_ptr),
+            [KEY2, b"\0\0\0\x04", VALUE2, b"\0\0\0\x05"].concat()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn do_db_next_returns_empty_item_for_exhausted_iterator() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        leave_default_data(&env);
+
+        let id = do_db_scan(&env, 0, 0, Order::Ascending.into()).unwrap();
+        do_db_next(&env, id).unwrap();
+        do_db_next(&env, id).unwrap();
+        let kv_region_ptr = do_db_next(&env, id).unwrap();
+        assert_eq!(force_read(&env, kv_region_ptr), b"\0\0\0\0\0\0\0\0");
+
+        // new data does not resurrect the exhausted iterator
+        env.with_storage_from_context::<_, _>(|store| {
+            store.set(b"zzz", b"new").0.unwrap();
+            Ok(())
+        })
+        .unwrap();
+        let kv_region_ptr = do_db_next(&env, id).unwrap();
+        assert_eq!(force_read(&env, kv_region_ptr), b"\0\0\0\0\0\0\0\0");
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn do_db_scan_rejects_non_increasing_iterator_ids() {
+        let api = MockApi::default();
+        let (env, _instance) = make_instance(api);
+
+        leave_default_data(&env);
+
+        let id = do_db_scan(&env, 0, 0, Order::Ascending.into()).unwrap();
+        let id2 = do_db_scan(&env, 0, 0, Order::Ascending.into()).unwrap();
+        assert!(id2 > id);
+
+        // an id reused by the backend
+        match env.record_new_iterator(id).unwrap_err() {
+            VmError::GenericErr { msg, .. } => assert_eq!(
+                msg,
+                format!(
+                    "Backend returned iterator id {} after id {}. Iterator ids must increase within a call.",
+                    id, id2
+                )
+            ),
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // ids start over in the next call
+        env.reset_iterators();
+        env.record_new_iterator(id).unwrap();
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn do_db_next_fails_for_non_existent_id() {
@@ -1,7 +1,9 @@
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::ptr::NonNull;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
+use cosmwasm_std::{Binary, CosmosMsg};
 use wasmer::{Exports, Function, ImportObject, Instance as WasmerInstance, Module, Val};
 
 use crate::backend::{Backend, BackendApi, Querier, Storage};
@@ -10,8 +12,9 @@ use crate::conversion::{ref_to_u32, to_u32};
 use crate::environment::Environment;
 use crate::errors::{CommunicationError, VmError, VmResult};
 use crate::imports::{
-    do_abort, do_addr_canonicalize, do_addr_humanize, do_addr_validate, do_db_read, do_db_remove,
-    do_db_write, do_debug, do_ed25519_batch_verify, do_ed25519_verify, do_query_chain,
+    do_abort, do_addr_canonicalize, do_addr_humanize, do_addr_validate, do_db_read,
+    do_db_read_range, do_db_remove, do_db_write, do_debug, do_ed25519_batch_verify,
+    do_ed25519_verify, do_get_block_height, do_get_block_time, do_query_chain,
     do_secp256k1_recover_pubkey, do_secp256k1_verify, do_sha1_calculate,
 };
 #[cfg(feature = "iterator")]
@@ -20,6 +23,22 @@ use crate::memory::{read_region, write_region};
 use crate::size::Size;
 use crate::wasm_backend::compile;
 
+/// Wraps the host function `$func` into a closure that charges at least the minimum import
+/// cost for each successful call and records the gas consumed under the import name `$name`
+/// when import gas tracking is enabled.
+/// The closure must not capture anything since wasmer does not support closures with state.
+macro_rules! tracked_import {
+    ($name:literal, $func:ident $(, $arg:ident: $ty:ty)*) => {
+        |env: &Environment<A, S, Q> $(, $arg: $ty)*| {
+            let checkpoint = env.import_gas_checkpoint();
+            let result = $func(env $(, $arg)*)
+                .and_then(|value| env.charge_min_import_cost(checkpoint).map(|_| value));
+            env.record_import_gas($name, checkpoint);
+            result
+        }
+    };
+}
+
 #[derive(Copy, Clone, Debug)]
 pub struct GasReport {
     /// The original limit the instance was created with
@@ -33,13 +52,115 @@ pub struct GasReport {
     pub used_internally: u64,
 }
 
-#[derive(Copy, Clone, Debug)]
+/// A check run by the host on every message returned from the execute entry point.
+/// An `Err` rejects the call with [`VmError::MessageRejected`] containing the error message.
+/// It is set per instance via [`InstanceOptions::msg_filter`].
+///
+/// Custom messages are passed as `CosmosMsg::Custom` with the JSON serialization of the payload.
+#[derive(Clone)]
+pub struct MsgFilter(Arc<MsgFilterFn>);
+
+type MsgFilterFn = dyn Fn(&CosmosMsg<Binary>) -> Result<(), String> + Send + Sync;
+
+impl MsgFilter {
+    pub fn new<F>(filter: F) -> Self
+    where
+        F: Fn(&CosmosMsg<Binary>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        MsgFilter(Arc::new(filter))
+    }
+
+    pub fn check(&self, msg: &CosmosMsg<Binary>) -> Result<(), String> {
+        (self.0)(msg)
+    }
+}
+
+impl fmt::Debug for MsgFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("MsgFilter")
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct InstanceOptions {
     /// Gas limit measured in [CosmWasm gas](https://github.com/CosmWasm/cosmwasm/blob/main/docs/GAS.md).
     pub gas_limit: u64,
     pub print_debug: bool,
+    /// An upper bound for the number of bytes copied out of a single Region by the VM.
+    /// This is applied on top of the limits used for the individual reads.
+    /// `None` means no additional limit.
+    pub max_region_read_len: Option<usize>,
+    /// Factor applied to all gas charged by the host, i.e. gas used by imports and backend calls.
+    /// Use 1 for regular pricing. 0 is not allowed.
+    pub gas_multiplier: u64,
+    /// Max length (in bytes) of the message passed to the instantiate entry point.
+    /// Longer messages are rejected before the contract is called. `None` means no limit.
+    pub max_instantiate_msg_len: Option<usize>,
+    /// Max length (in bytes) of the message passed to the execute entry point.
+    /// Longer messages are rejected before the contract is called. `None` means no limit.
+    pub max_execute_msg_len: Option<usize>,
+    /// Max number of signatures, messages or public keys in a single ed25519_batch_verify call.
+    /// This can only lower the built-in limit of 256. `None` uses the built-in limit.
+    pub max_ed25519_batch_size: Option<usize>,
+    /// An upper bound for the total number of bytes the VM allocates in the contract during a
+    /// single call, e.g. for storage reads and query responses. Allocations made by the contract
+    /// itself are not counted. `None` means no limit.
+    pub max_vm_allocations_bytes: Option<usize>,
+    /// Do not charge the gas reported by the querier for `query_chain` calls.
+    /// Meant for gas estimation of query heavy contracts. Defaults to false.
+    pub free_queries: bool,
+    /// Max number of messages in the response returned by execute. Responses with more
+    /// messages are rejected. `None` means no limit. See [`DEFAULT_MAX_MESSAGES_PER_RESPONSE`]
+    /// for a suggested value.
+    pub max_messages_per_response: Option<usize>,
+    /// Max number of `db_next` calls per iterator. This is a defense in depth on top of the
+    /// gas charged for every step. `None` means no limit.
+    pub max_iterator_steps: Option<usize>,
+    /// Prepend a reserved `_contract_address` attribute with the address from `Env` to every
+    /// event returned by the typed calls, e.g. [`crate::call_execute`]. Defaults to false.
+    pub auto_tag_events: bool,
+    /// Max length (in bytes) of a serialized query response written into the contract by
+    /// `query_chain`. Longer responses fail the call independent of gas. `None` means no limit.
+    pub max_query_response_len: Option<usize>,
+    /// Checked on every message of the response returned by execute.
+    /// `None` accepts all messages.
+    pub msg_filter: Option<MsgFilter>,
+}
+
+impl InstanceOptions {
+    /// Regular gas pricing with all optional limits and features disabled.
+    /// This can be used in `const` contexts, e.g.
+    /// `InstanceOptions { gas_limit, ..InstanceOptions::DEFAULT }`.
+    ///
+    /// The gas limit is 0, so `gas_limit` should always be set.
+    pub const DEFAULT: InstanceOptions = InstanceOptions {
+        gas_limit: 0,
+        print_debug: false,
+        max_region_read_len: None,
+        gas_multiplier: 1,
+        max_instantiate_msg_len: None,
+        max_execute_msg_len: None,
+        max_ed25519_batch_size: None,
+        max_vm_allocations_bytes: None,
+        free_queries: false,
+        max_messages_per_response: None,
+        max_iterator_steps: None,
+        auto_tag_events: false,
+        max_query_response_len: None,
+        msg_filter: None,
+    };
+}
+
+/// Same as [`InstanceOptions::DEFAULT`]
+impl Default for InstanceOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
 }
 
+/// A generous limit for [`InstanceOptions::max_messages_per_response`]
+pub const DEFAULT_MAX_MESSAGES_PER_RESPONSE: usize = 100;
+
 pub struct Instance<A: BackendApi, S: Storage, Q: Querier> {
     /// We put this instance in a box to maintain a constant memory address for the entire
     /// lifetime of the instance in the cache. This is needed e.g. when linking the wasmer
@@ -48,6 +169,13 @@ pub struct Instance<A: BackendApi, S: Storage, Q: Querier> {
     /// This instance should only be accessed via the Environment, which provides safe access.
     _inner: Box<WasmerInstance>,
     env: Environment<A, S, Q>,
+    max_instantiate_msg_len: Option<usize>,
+    max_execute_msg_len: Option<usize>,
+    msg_filter: Option<MsgFilter>,
+    max_messages_per_response: Option<usize>,
+    auto_tag_events: bool,
+    /// Whether the contract imports `get_block_time` or `get_block_height` and thus needs the env
+    uses_block_info: bool,
 }
 
 impl<A, S, Q> Instance<A, S, Q>
@@ -65,27 +193,32 @@ where
         memory_limit: Option<Size>,
     ) -> VmResult<Self> {
         let module = compile(code, memory_limit, &[])?;
-        Instance::from_module(
-            &module,
-            backend,
-            options.gas_limit,
-            options.print_debug,
-            None,
-            None,
-        )
+        Instance::from_module(&module, backend, options, None, None)
     }
 
     pub(crate) fn from_module(
         module: &Module,
         backend: Backend<A, S, Q>,
-        gas_limit: u64,
-        print_debug: bool,
+        options: InstanceOptions,
         extra_imports: Option<HashMap<&str, Exports>>,
         instantiation_lock: Option<&Mutex<()>>,
     ) -> VmResult<Self> {
+        if options.gas_multiplier == 0 {
+            return Err(VmError::instantiation_err(
+                "Gas multiplier must not be zero",
+            ));
+        }
+
         let store = module.store();
 
-        let env = Environment::new(backend.api, gas_limit, print_debug);
+        let mut env = Environment::new(backend.api, options.gas_limit, options.print_debug);
+        env.max_region_read_len = options.max_region_read_len;
+        env.gas_multiplier = options.gas_multiplier;
+        env.max_ed25519_batch_size = options.max_ed25519_batch_size;
+        env.max_vm_allocations_bytes = options.max_vm_allocations_bytes;
+        env.free_queries = options.free_queries;
+        env.max_iterator_steps = options.max_iterator_steps;
+        env.max_query_response_len = options.max_query_response_len;
 
         let mut import_obj = ImportObject::new();
         let mut env_imports = Exports::new();
@@ -96,14 +229,36 @@ where
         // Ownership of the value pointer is transferred to the contract.
         env_imports.insert(
             "db_read",
-            Function::new_native_with_env(store, env.clone(), do_db_read),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_read", do_db_read, key_ptr: u32),
+            ),
+        );
+
+        // Reads `length` bytes starting at `offset` of the database entry at the given key.
+        // Returns 0 if key does not exist and pointer to result region otherwise.
+        // The result is empty if `offset` is beyond the end of the value.
+        // Ownership of the key pointer is not transferred to the host.
+        // Ownership of the value pointer is transferred to the contract.
+        env_imports.insert(
+            "db_read_range",
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_read_range", do_db_read_range, key_ptr: u32, offset: u32, length: u32),
+            ),
         );
 
         // Writes the given value into the database entry at the given key.
         // Ownership of both input and output pointer is not transferred to the host.
         env_imports.insert(
             "db_write",
-            Function::new_native_with_env(store, env.clone(), do_db_write),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_write", do_db_write, key_ptr: u32, value_ptr: u32),
+            ),
         );
 
         // Removes the value at the given key. Different than writing &[] as future
@@ -112,7 +267,11 @@ where
         // Ownership of both key pointer is not transferred to the host.
         env_imports.insert(
             "db_remove",
-            Function::new_native_with_env(store, env.clone(), do_db_remove),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_remove", do_db_remove, key_ptr: u32),
+            ),
         );
 
         // Reads human address from source_ptr and checks if it is valid.
@@ -120,7 +279,11 @@ where
         // Ownership of the input pointer is not transferred to the host.
         env_imports.insert(
             "addr_validate",
-            Function::new_native_with_env(store, env.clone(), do_addr_validate),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("addr_validate", do_addr_validate, source_ptr: u32),
+            ),
         );
 
         // Reads human address from source_ptr and writes canonicalized representation to destination_ptr.
@@ -129,7 +292,11 @@ where
         // Ownership of both input and output pointer is not transferred to the host.
         env_imports.insert(
             "addr_canonicalize",
-            Function::new_native_with_env(store, env.clone(), do_addr_canonicalize),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("addr_canonicalize", do_addr_canonicalize, source_ptr: u32, destination_ptr: u32),
+            ),
         );
 
         // Reads canonical address from source_ptr and writes humanized representation to destination_ptr.
@@ -138,7 +305,11 @@ where
         // Ownership of both input and output pointer is not transferred to the host.
         env_imports.insert(
             "addr_humanize",
-            Function::new_native_with_env(store, env.clone(), do_addr_humanize),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("addr_humanize", do_addr_humanize, source_ptr: u32, destination_ptr: u32),
+            ),
         );
 
         // Verifies message hashes against a signature with a public key, using the secp256k1 ECDSA parametrization.
@@ -146,12 +317,20 @@ where
         // Ownership of input pointers is not transferred to the host.
         env_imports.insert(
             "secp256k1_verify",
-            Function::new_native_with_env(store, env.clone(), do_secp256k1_verify),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("secp256k1_verify", do_secp256k1_verify, hash_ptr: u32, signature_ptr: u32, pubkey_ptr: u32),
+            ),
         );
 
         env_imports.insert(
             "secp256k1_recover_pubkey",
-            Function::new_native_with_env(store, env.clone(), do_secp256k1_recover_pubkey),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("secp256k1_recover_pubkey", do_secp256k1_recover_pubkey, hash_ptr: u32, signature_ptr: u32, recover_param: u32),
+            ),
         );
 
         // Verifies a message against a signature with a public key, using the ed25519 EdDSA scheme.
@@ -159,7 +338,11 @@ where
         // Ownership of input pointers is not transferred to the host.
         env_imports.insert(
             "ed25519_verify",
-            Function::new_native_with_env(store, env.clone(), do_ed25519_verify),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("ed25519_verify", do_ed25519_verify, message_ptr: u32, signature_ptr: u32, pubkey_ptr: u32),
+            ),
         );
 
         // Verifies a batch of messages against a batch of signatures with a batch of public keys,
@@ -169,7 +352,11 @@ where
         // Ownership of input pointers is not transferred to the host.
         env_imports.insert(
             "ed25519_batch_verify",
-            Function::new_native_with_env(store, env.clone(), do_ed25519_batch_verify),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("ed25519_batch_verify", do_ed25519_batch_verify, messages_ptr: u32, signatures_ptr: u32, public_keys_ptr: u32),
+            ),
         );
 
         // Calculates the inputs using the sha1
@@ -178,7 +365,11 @@ where
         // Ownership of the hash pointer is transferred to the contract.
         env_imports.insert(
             "sha1_calculate",
-            Function::new_native_with_env(store, env.clone(), do_sha1_calculate),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("sha1_calculate", do_sha1_calculate, hash_inputs_ptr: u32),
+            ),
         );
 
         // Allows the contract to emit debug logs that the host can either process or ignore.
@@ -187,7 +378,11 @@ where
         // Ownership of both input and output pointer is not transferred to the host.
         env_imports.insert(
             "debug",
-            Function::new_native_with_env(store, env.clone(), do_debug),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("debug", do_debug, message_ptr: u32),
+            ),
         );
 
         // Aborts the contract execution with an error message provided by the contract.
@@ -195,12 +390,42 @@ where
         // Ownership of both input and output pointer is not transferred to the host.
         env_imports.insert(
             "abort",
-            Function::new_native_with_env(store, env.clone(), do_abort),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("abort", do_abort, message_ptr: u32),
+            ),
+        );
+
+        // Returns the block time of the current call in nanoseconds since epoch as u64.
+        // This avoids deserializing the full env in the contract.
+        env_imports.insert(
+            "get_block_time",
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("get_block_time", do_get_block_time),
+            ),
+        );
+
+        // Returns the block height of the current call as u64.
+        // This avoids deserializing the full env in the contract.
+        env_imports.insert(
+            "get_block_height",
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("get_block_height", do_get_block_height),
+            ),
         );
 
         env_imports.insert(
             "query_chain",
-            Function::new_native_with_env(store, env.clone(), do_query_chain),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("query_chain", do_query_chain, request_ptr: u32),
+            ),
         );
 
         // Creates an iterator that will go from start to end.
@@ -212,7 +437,11 @@ where
         #[cfg(feature = "iterator")]
         env_imports.insert(
             "db_scan",
-            Function::new_native_with_env(store, env.clone(), do_db_scan),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_scan", do_db_scan, start_ptr: u32, end_ptr: u32, order: i32),
+            ),
         );
 
         // Get next element of iterator with ID `iterator_id`.
@@ -223,7 +452,11 @@ where
         #[cfg(feature = "iterator")]
         env_imports.insert(
             "db_next",
-            Function::new_native_with_env(store, env.clone(), do_db_next),
+            Function::new_native_with_env(
+                store,
+                env.clone(),
+                tracked_import!("db_next", do_db_next, iterator_id: u32),
+            ),
         );
 
         import_obj.register("env", env_imports);
@@ -234,6 +467,11 @@ where
             }
         }
 
+        let uses_block_info = module.imports().any(|import| {
+            import.module() == "env"
+                && matches!(import.name(), "get_block_time" | "get_block_height")
+        });
+
         let wasmer_instance = Box::from(
             {
                 let _lock = instantiation_lock.map(|l| l.lock().unwrap());
@@ -246,11 +484,17 @@ where
 
         let instance_ptr = NonNull::from(wasmer_instance.as_ref());
         env.set_wasmer_instance(Some(instance_ptr));
-        env.set_gas_left(gas_limit);
+        env.set_gas_left(options.gas_limit);
         env.move_in(backend.storage, backend.querier);
         let instance = Instance {
             _inner: wasmer_instance,
             env,
+            max_instantiate_msg_len: options.max_instantiate_msg_len,
+            max_execute_msg_len: options.max_execute_msg_len,
+            msg_filter: options.msg_filter,
+            max_messages_per_response: options.max_messages_per_response,
+            auto_tag_events: options.auto_tag_events,
+            uses_block_info,
         };
         Ok(instance)
     }
@@ -323,6 +567,12 @@ where
         self.env.set_storage_readonly(new_value);
     }
 
+    /// Returns true iff the storage is set to readonly mode, i.e. the contract
+    /// can neither write to storage nor emit events.
+    pub fn is_storage_readonly(&self) -> bool {
+        self.env.is_storage_readonly()
+    }
+
     pub fn with_storage<F: FnOnce(&mut S) -> VmResult<T>, T>(&mut self, func: F) -> VmResult<T> {
         self.env.with_storage_from_context::<F, T>(func)
     }
@@ -331,6 +581,89 @@ where
         self.env.with_querier_from_context::<F, T>(func)
     }
 
+    /// Max length of the message passed to the instantiate entry point, if limited
+    pub(crate) fn max_instantiate_msg_len(&self) -> Option<usize> {
+        self.max_instantiate_msg_len
+    }
+
+    /// Max length of the message passed to the execute entry point, if limited
+    pub(crate) fn max_execute_msg_len(&self) -> Option<usize> {
+        self.max_execute_msg_len
+    }
+
+    /// The filter for messages returned by execute, if any
+    pub(crate) fn msg_filter(&self) -> Option<&MsgFilter> {
+        self.msg_filter.as_ref()
+    }
+
+    /// Max number of messages in the response returned by execute, if limited
+    pub(crate) fn max_messages_per_response(&self) -> Option<usize> {
+        self.max_messages_per_response
+    }
+
+    /// Whether events returned by the typed calls get tagged with the contract address
+    pub(crate) fn auto_tag_events(&self) -> bool {
+        self.auto_tag_events
+    }
+
+    /// Resets the number of bytes the VM allocated in the contract, which is
+    /// limited per call by `max_vm_allocations_bytes`.
+    pub(crate) fn reset_vm_allocated_bytes(&self) {
+        self.env.reset_vm_allocated_bytes()
+    }
+
+    /// Forgets the last message printed via `debug`, which is used to explain traps.
+    pub(crate) fn reset_last_debug_message(&self) {
+        self.env.reset_last_debug_message()
+    }
+
+    /// Starts recording the gas consumed by each import, discarding previous records.
+    pub(crate) fn start_import_gas_tracking(&self) {
+        self.env.start_import_gas_tracking()
+    }
+
+    /// Stops recording and returns the gas consumed by each import since
+    /// tracking was started.
+    pub(crate) fn stop_import_gas_tracking(&self) -> HashMap<&'static str, u64> {
+        self.env.stop_import_gas_tracking()
+    }
+
+    /// Forgets the iterators of previous calls
+    #[cfg(feature = "iterator")]
+    pub(crate) fn reset_iterators(&self) {
+        self.env.reset_iterators()
+    }
+
+    /// Restores the full gas limit of this instance for the next call
+    pub(crate) fn reset_gas(&self) {
+        let gas_limit = self.env.with_gas_state(|gas_state| gas_state.gas_limit);
+        self.env.reset_gas(gas_limit)
+    }
+
+    /// Sets the serialized env of the current call, which is used by the block info imports.
+    /// Does nothing if the contract does not import them, so that other contracts do not pay
+    /// for copying the env.
+    pub(crate) fn set_serialized_env(&self, serialized_env: &[u8]) {
+        if self.uses_block_info {
+            self.env.set_serialized_env(serialized_env)
+        }
+    }
+
+    /// Starts recording the storage keys read and written by the contract for
+    /// [`Instance::access_list`], discarding previous records.
+    pub fn enable_access_list(&mut self) {
+        self.env.enable_access_list()
+    }
+
+    /// Returns the storage keys read and written (or removed) by the contract since
+    /// [`Instance::enable_access_list`] was called, each sorted and without duplicates.
+    /// Keys visited through iterators are not included.
+    ///
+    /// Both lists are empty if the access list was never enabled.
+    pub fn access_list(&self) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        self.env.access_list()
+    }
+
     /// Requests memory allocation by the instance and returns a pointer
     /// in the Wasm address space to the created Region object.
     pub(crate) fn allocate(&mut self, size: usize) -> VmResult<u32> {
@@ -352,7 +685,11 @@ where
 
     /// Copies all data described by the Region at the given pointer from Wasm to the caller.
     pub(crate) fn read_memory(&self, region_ptr: u32, max_length: usize) -> VmResult<Vec<u8>> {
-        read_region(&self.env.memory(), region_ptr, max_length)
+        read_region(
+            &self.env.memory(),
+            region_ptr,
+            self.env.region_read_limit(max_length),
+        )
     }
 
     /// Copies data to the memory region that was created before using allocate.
@@ -361,6 +698,21 @@ where
         Ok(())
     }
 
+    /// Returns true if the instance exports a function with the given name.
+    pub(crate) fn has_function(&self, name: &str) -> bool {
+        self._inner.exports.get_function(name).is_ok()
+    }
+
+    /// Returns the number of parameters of the exported function with the given name,
+    /// or `None` if there is no such function.
+    pub(crate) fn function_arity(&self, name: &str) -> Option<usize> {
+        self._inner
+            .exports
+            .get_function(name)
+            .ok()
+            .map(|function| function.ty().params().len())
+    }
+
     /// Calls a function exported by the instance.
     /// The function is expected to return no value. Otherwise this calls errors.
     pub(crate) fn call_function0(&self, name: &str, args: &[Val]) -> VmResult<()> {
@@ -388,7 +740,12 @@ where
     S: Storage + 'static, // 'static is needed here to allow using this in an Environment that is cloned into closures
     Q: Querier + 'static,
 {
-    Instance::from_module(module, backend, gas_limit, print_debug, extra_imports, None)
+    let options = InstanceOptions {
+        gas_limit,
+        print_debug,
+        ..Default::default()
+    };
+    Instance::from_module(module, backend, options, extra_imports, None)
 }
 
 #[cfg(test)]
@@ -398,15 +755,15 @@ mod tests {
 
     use super::*;
     use crate::backend::Storage;
-    use crate::calls::{call_execute, call_instantiate, call_query};
+    use crate::calls::{call_execute, call_execute_raw, call_instantiate, call_query};
     use crate::errors::VmError;
     use crate::testing::{
-        mock_backend, mock_env, mock_info, mock_instance, mock_instance_options,
-        mock_instance_with_balances, mock_instance_with_failing_api, mock_instance_with_gas_limit,
-        mock_instance_with_options, MockInstanceOptions,
+        bump_allocator_contract, mock_backend, mock_env, mock_info, mock_instance,
+        mock_instance_options, mock_instance_with_balances, mock_instance_with_failing_api,
+        mock_instance_with_gas_limit, mock_instance_with_options, MockInstanceOptions,
     };
     use cosmwasm_std::{
-        coin, coins, from_binary, AllBalanceResponse, BalanceResponse, BankQuery, Empty,
+        coin, coins, from_binary, to_vec, AllBalanceResponse, BalanceResponse, BankQuery, Empty,
         QueryRequest,
     };
 
@@ -481,11 +838,14 @@ mod tests {
         exports.insert("bar", fun);
         let mut extra_imports = HashMap::new();
         extra_imports.insert("foo", exports);
+        let instance_options = InstanceOptions {
+            print_debug: false,
+            ..instance_options
+        };
         let instance = Instance::from_module(
             &module,
             backend,
-            instance_options.gas_limit,
-            false,
+            instance_options,
             Some(extra_imports),
             None,
         )
@@ -531,10 +891,7 @@ mod tests {
     fn allocate_deallocate_works() {
         let mut instance = mock_instance_with_options(
             CONTRACT,
-            MockInstanceOptions {
-                memory_limit: Some(Size::mebi(500)),
-                ..Default::default()
-            },
+            MockInstanceOptions::default().with_memory_limit(Size::mebi(500)),
         );
 
         let sizes: Vec<usize> = vec![
@@ -635,6 +992,170 @@ mod tests {
         instance.deallocate(region_ptr).expect("error deallocating");
     }
 
+    #[test]
+    fn from_code_rejects_zero_gas_multiplier() {
+        let (instance_options, memory_limit) = mock_instance_options();
+        let options = InstanceOptions {
+            gas_multiplier: 0,
+            ..instance_options
+        };
+        match Instance::from_code(CONTRACT, mock_backend(&[]), options, memory_limit) {
+            Err(VmError::InstantiationErr { msg, .. }) => {
+                assert_eq!(msg, "Gas multiplier must not be zero")
+            }
+            Err(err) => panic!("Unexpected error: {:?}", err),
+            Ok(_) => panic!("Expected error"),
+        }
+    }
+
+    #[test]
+    fn read_memory_respects_max_region_read_len() {
+        let length = 6;
+        let mut instance = mock_instance_with_options(
+            CONTRACT,
+            MockInstanceOptions {
+                instance_options: InstanceOptions {
+                    max_region_read_len: Some(5),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let region_ptr = instance.allocate(length).expect("error allocating");
+        let data = vec![170u8; length];
+        instance
+            .write_memory(region_ptr, &data)
+            .expect("error writing");
+
+        // max_length passed by the caller is huge but capped by the instance option
+        let result = instance.read_memory(region_ptr, u32::MAX as usize);
+        match result.unwrap_err() {
+            VmError::CommunicationErr {
+                source:
+                    CommunicationError::RegionLengthTooBig {
+                        length, max_length, ..
+                    },
+                ..
+            } => {
+                assert_eq!(length, 6);
+                assert_eq!(max_length, 5);
+            }
+            err => panic!("unexpected error: {:?}", err),
+        };
+
+        instance.deallocate(region_ptr).expect("error deallocating");
+    }
+
+    #[test]
+    fn call_function_reports_traps() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory 0)
+                (export "memory" (memory 0))
+
+                (func $nop nop)
+                (func $allocate (param i32) (result i32) local.get 0)
+                (func $deallocate (param i32))
+                (func $explode unreachable)
+                (func $call_explode call $explode)
+                (export "interface_version_8" (func $nop))
+                (export "instantiate" (func $nop))
+                (export "allocate" (func $allocate))
+                (export "deallocate" (func $deallocate))
+                (export "call_explode" (func $call_explode))
+            )"#,
+        )
+        .unwrap();
+        let instance = mock_instance(&wasm, &[]);
+
+        match instance.call_function0("call_explode", &[]).unwrap_err() {
+            #[cfg(not(feature = "backtraces"))]
+            VmError::RuntimeErr { msg, .. } => {
+                assert_eq!(
+                    msg,
+                    "Wasmer runtime error: RuntimeError: unreachable. Contract likely panicked."
+                );
+            }
+            #[cfg(feature = "backtraces")]
+            VmError::RuntimeErr { msg, frames, .. } => {
+                assert_eq!(
+                    msg,
+                    "Wasmer runtime error: RuntimeError: unreachable. Contract likely panicked."
+                );
+                assert_eq!(frames.len(), 2);
+                assert!(frames[0].starts_with("explode ("), "{}", frames[0]);
+                assert!(frames[1].starts_with("call_explode ("), "{}", frames[1]);
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn access_list_works() {
+        let wasm = bump_allocator_contract(
+            r#"
+                (import "env" "db_read" (func $db_read (param i32) (result i32)))
+                (import "env" "db_write" (func $db_write (param i32 i32)))
+            "#,
+            r#"
+                (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 256))
+                ;; reads "foo" and writes "foo" to "bar"
+                (func (export "execute") (param i32 i32 i32) (result i32)
+                    (drop (call $db_read (i32.const 320)))
+                    (call $db_write (i32.const 360) (i32.const 320))
+                    (i32.const 256))
+                ;; Region { offset: 512, capacity: 0, length: 0 }
+                (data (i32.const 256) "\00\02\00\00\00\00\00\00\00\00\00\00")
+                ;; Region { offset: 300, capacity: 3, length: 3 } containing "foo"
+                (data (i32.const 300) "foo")
+                (data (i32.const 320) "\2c\01\00\00\03\00\00\00\03\00\00\00")
+                ;; Region { offset: 340, capacity: 3, length: 3 } containing "bar"
+                (data (i32.const 340) "bar")
+                (data (i32.const 360) "\54\01\00\00\03\00\00\00\03\00\00\00")
+            "#,
+        );
+        let mut instance = mock_instance(&wasm, &[]);
+        let env = to_vec(&mock_env()).unwrap();
+        let info = to_vec(&mock_info("creator", &[])).unwrap();
+
+        // not enabled
+        call_execute_raw(&mut instance, &env, &info, b"{}").unwrap();
+        assert_eq!(instance.access_list(), (vec![], vec![]));
+
+        instance.enable_access_list();
+        call_execute_raw(&mut instance, &env, &info, b"{}").unwrap();
+        let (reads, writes) = instance.access_list();
+        assert_eq!(reads, vec![b"foo".to_vec()]);
+        assert_eq!(writes, vec![b"bar".to_vec()]);
+
+        // keys are not duplicated across calls
+        call_execute_raw(&mut instance, &env, &info, b"{}").unwrap();
+        assert_eq!(instance.access_list(), (reads, writes));
+
+        // enabling again clears the list
+        instance.enable_access_list();
+        assert_eq!(instance.access_list(), (vec![], vec![]));
+    }
+
+    #[test]
+    fn set_serialized_env_only_stores_env_for_block_info_imports() {
+        let env = to_vec(&mock_env()).unwrap();
+
+        // hackatom does not import the block info functions
+        let instance = mock_instance(CONTRACT, &[]);
+        instance.set_serialized_env(&env);
+        instance.env.block_info().unwrap_err();
+
+        let wasm = bump_allocator_contract(
+            r#"(import "env" "get_block_height" (func $get_block_height (result i64)))"#,
+            r#"(func (export "instantiate") (param i32 i32 i32) (result i32) unreachable)"#,
+        );
+        let instance = mock_instance(&wasm, &[]);
+        instance.set_serialized_env(&env);
+        assert_eq!(instance.env.block_info().unwrap(), mock_env().block);
+    }
+
     #[test]
     fn memory_pages_returns_min_memory_size_by_default() {
         // min: 0 pages, max: none
@@ -645,10 +1166,12 @@ mod tests {
 
                 (type (func))
                 (func (type 0) nop)
+                (func (param i32) (result i32) local.get 0)
+                (func (param i32))
                 (export "interface_version_8" (func 0))
                 (export "instantiate" (func 0))
-                (export "allocate" (func 0))
-                (export "deallocate" (func 0))
+                (export "allocate" (func 1))
+                (export "deallocate" (func 2))
             )"#,
         )
         .unwrap();
@@ -663,10 +1186,12 @@ mod tests {
 
                 (type (func))
                 (func (type 0) nop)
+                (func (param i32) (result i32) local.get 0)
+                (func (param i32))
                 (export "interface_version_8" (func 0))
                 (export "instantiate" (func 0))
-                (export "allocate" (func 0))
-                (export "deallocate" (func 0))
+                (export "allocate" (func 1))
+                (export "deallocate" (func 2))
             )"#,
         )
         .unwrap();
@@ -717,7 +1242,7 @@ mod tests {
 
         let report2 = instance.create_gas_report();
         assert_eq!(report2.used_externally, 73);
-        assert_eq!(report2.used_internally, 5775750198);
+        assert_eq!(report2.used_internally, 5775750199);
         assert_eq!(report2.limit, LIMIT);
         assert_eq!(
             report2.remaining,
@@ -906,7 +1431,7 @@ mod tests {
             .unwrap();
 
         let init_used = orig_gas - instance.get_gas_left();
-        assert_eq!(init_used, 5775750271);
+        assert_eq!(init_used, 5775750272);
     }
 
     #[test]
@@ -921,14 +1446,14 @@ mod tests {
             .unwrap();
 
         // run contract - just sanity check - results validate in contract unit tests
-        let gas_before_execute = instance.get_gas_left();
         let info = mock_info("verifies", &coins(15, "earth"));
         let msg = br#"{"release":{}}"#;
         call_execute::<_, _, _, Empty>(&mut instance, &mock_env(), &info, msg)
             .unwrap()
             .unwrap();
 
-        let execute_used = gas_before_execute - instance.get_gas_left();
+        // every call starts with the full gas limit
+        let execute_used = instance.create_gas_report().limit - instance.get_gas_left();
         assert_eq!(execute_used, 8627053606);
     }
 
@@ -955,14 +1480,14 @@ mod tests {
             .unwrap();
 
         // run contract - just sanity check - results validate in contract unit tests
-        let gas_before_query = instance.get_gas_left();
         // we need to encode the key in base64
         let msg = br#"{"verifier":{}}"#;
         let res = call_query(&mut instance, &mock_env(), msg).unwrap();
         let answer = res.unwrap();
         assert_eq!(answer.as_slice(), b"{\"verifier\":\"verifies\"}");
 
-        let query_used = gas_before_query - instance.get_gas_left();
+        // every call starts with the full gas limit
+        let query_used = instance.create_gas_report().limit - instance.get_gas_left();
         assert_eq!(query_used, 4438350006);
     }
 }
@@ -24,11 +24,11 @@ mod wasm_backend;
 pub use crate::backend::{
     Backend, BackendApi, BackendError, BackendResult, GasInfo, Querier, Storage,
 };
-pub use crate::cache::{AnalysisReport, Cache, CacheOptions, Metrics, Stats};
+pub use crate::cache::{AnalysisReport, Cache, CacheOptions, CapabilityDiff, Metrics, Stats};
 pub use crate::calls::{
     call_execute, call_execute_raw, call_instantiate, call_instantiate_raw, call_migrate,
-    call_migrate_raw, call_query, call_query_raw, call_reply, call_reply_raw, call_sudo,
-    call_sudo_raw,
+    call_migrate_raw, call_migrate_with_info, call_migrate_with_info_raw, call_query,
+    call_query_raw, call_reply, call_reply_raw, call_sudo, call_sudo_raw,
 };
 #[cfg(feature = "stargate")]
 pub use crate::calls::{
@@ -37,15 +37,19 @@ pub use crate::calls::{
     call_ibc_packet_ack, call_ibc_packet_ack_raw, call_ibc_packet_receive,
     call_ibc_packet_receive_raw, call_ibc_packet_timeout, call_ibc_packet_timeout_raw,
 };
-pub use crate::capabilities::capabilities_from_csv;
+pub use crate::capabilities::{capabilities_from_csv, contract_feature_exports, ContractFeatures};
 pub use crate::checksum::Checksum;
+pub use crate::compatibility::DEFAULT_MAX_DATA_SEGMENTS_SIZE;
 pub use crate::errors::{
     CommunicationError, CommunicationResult, RegionValidationError, RegionValidationResult,
     VmError, VmResult,
 };
-pub use crate::instance::{GasReport, Instance, InstanceOptions};
+pub use crate::instance::{
+    GasReport, Instance, InstanceOptions, MsgFilter, DEFAULT_MAX_MESSAGES_PER_RESPONSE,
+};
 pub use crate::serde::{from_slice, to_vec};
 pub use crate::size::Size;
+pub use crate::static_analysis::read_custom_section;
 
 #[doc(hidden)]
 pub mod internals {
@@ -54,7 +58,10 @@ pub mod internals {
     //! Please don't use any of these types directly, as
     //! they might change frequently or be removed in the future.
 
-    pub use crate::compatibility::check_wasm;
+    pub use crate::compatibility::{
+        check_wasm, check_wasm_report, check_wasm_with_imports, check_wasm_with_legacy_toggle,
+        ValidationReport,
+    };
     pub use crate::instance::instance_from_module;
     pub use crate::wasm_backend::{compile, make_runtime_store};
 }
@@ -1,9 +1,10 @@
-use wasmer::{Array, ValueType, WasmPtr};
+use std::io::Write;
+use wasmer::{Array, ValueType, WasmCell, WasmPtr};
 
 use crate::conversion::to_u32;
 use crate::errors::{
     CommunicationError, CommunicationResult, RegionValidationError, RegionValidationResult,
-    VmResult,
+    VmError, VmResult,
 };
 
 /****** read/write to wasm memory buffer ****/
@@ -101,6 +102,74 @@ pub fn write_region(memory: &wasmer::Memory, ptr: u32, data: &[u8]) -> VmResult<
     }
 }
 
+/// Like [`write_region`] but the data is written by the given callback directly into
+/// the Region, avoiding an intermediate buffer on the host side.
+///
+/// `length` is the exact number of bytes the callback writes. Writing more or less is an error.
+pub fn write_region_with<F>(
+    memory: &wasmer::Memory,
+    ptr: u32,
+    length: usize,
+    write: F,
+) -> VmResult<()>
+where
+    F: FnOnce(&mut dyn Write) -> VmResult<()>,
+{
+    let mut region = get_region(memory, ptr)?;
+
+    let region_capacity = region.capacity as usize;
+    if length > region_capacity {
+        return Err(CommunicationError::region_too_small(region_capacity, length).into());
+    }
+    match WasmPtr::<u8, Array>::new(region.offset).deref(memory, 0, region.capacity) {
+        Some(cells) => {
+            let mut writer = CellWriter {
+                cells: &cells[..length],
+                position: 0,
+            };
+            write(&mut writer)?;
+            if writer.position != length {
+                return Err(VmError::generic_err(format!(
+                    "Expected to write {} bytes into region but got {}",
+                    length, writer.position
+                )));
+            }
+            region.length = length as u32;
+            set_region(memory, ptr, region)?;
+            Ok(())
+        },
+        None => Err(CommunicationError::deref_err(region.offset, format!(
+            "Tried to access memory of region {:?} in wasm memory of size {} bytes. This typically happens when the given Region pointer does not point to a proper Region struct.",
+            region,
+            memory.size().bytes().0
+        )).into()),
+    }
+}
+
+/// Writes into a fixed size slice of Wasm memory
+struct CellWriter<'a> {
+    cells: &'a [WasmCell<'a, u8>],
+    position: usize,
+}
+
+impl Write for CellWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let remaining = &self.cells[self.position..];
+        if buf.len() > remaining.len() {
+            return Err(std::io::ErrorKind::WriteZero.into());
+        }
+        for (cell, byte) in remaining.iter().zip(buf) {
+            cell.set(*byte);
+        }
+        self.position += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 /// Reads in a Region at ptr in wasm memory and returns a copy of it
 fn get_region(memory: &wasmer::Memory, ptr: u32) -> CommunicationResult<Region> {
     let wptr = WasmPtr::<Region>::new(ptr);
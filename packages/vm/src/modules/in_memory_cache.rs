@@ -26,6 +26,12 @@ impl WeightScale<Checksum, SizedModule> for SizeScale {
 }
 
 /// An in-memory module cache
+///
+/// When the cache is full, the least recently used modules are evicted first. Storing or
+/// loading a module makes it the most recently used one. Modules that were never loaded
+/// are evicted in the order they were stored, i.e. oldest first. The eviction order does
+/// not depend on the checksums, so nodes processing the same operations evict the same
+/// modules.
 pub struct InMemoryCache {
     modules: Option<CLruCache<Checksum, SizedModule, RandomState, SizeScale>>,
 }
@@ -289,4 +295,47 @@ mod tests {
             .unwrap();
         assert_eq!(cache.size(), 1_500_000);
     }
+
+    #[test]
+    fn eviction_order_is_deterministic() {
+        let mut cache = InMemoryCache::new(Size::mebi(2));
+
+        let mut wasms: Vec<(Checksum, Vec<u8>)> = (1..=4)
+            .map(|n| {
+                let wasm = wat::parse_str(format!(
+                    r#"(module
+                    (type $t0 (func (param i32) (result i32)))
+                    (func $add (export "add") (type $t0) (param $p0 i32) (result i32)
+                        get_local $p0
+                        i32.const {}
+                        i32.add)
+                    )"#,
+                    n
+                ))
+                .unwrap();
+                (Checksum::generate(&wasm), wasm)
+            })
+            .collect();
+        // Store in descending checksum order to show the order of storage is used
+        wasms.sort_by_key(|(checksum, _)| std::cmp::Reverse(checksum.to_hex()));
+
+        // Three modules that are never loaded fill the cache
+        for (checksum, wasm) in &wasms[0..3] {
+            cache
+                .store(checksum, compile(wasm, None, &[]).unwrap(), 600_000)
+                .unwrap();
+        }
+        assert_eq!(cache.len(), 3);
+
+        // The oldest one is evicted
+        let (checksum, wasm) = &wasms[3];
+        cache
+            .store(checksum, compile(wasm, None, &[]).unwrap(), 600_000)
+            .unwrap();
+        assert_eq!(cache.len(), 3);
+        assert!(cache.load(&wasms[0].0).unwrap().is_none());
+        assert!(cache.load(&wasms[1].0).unwrap().is_some());
+        assert!(cache.load(&wasms[2].0).unwrap().is_some());
+        assert!(cache.load(&wasms[3].0).unwrap().is_some());
+    }
 }
@@ -4,6 +4,7 @@
 //! 2. To allow us to switch out to eg. serde-json-core more easily
 use serde::{Deserialize, Serialize};
 use std::any::type_name;
+use std::io::Write;
 
 use crate::errors::{VmError, VmResult};
 
@@ -31,6 +32,39 @@ where
     serde_json::to_vec(data).map_err(|e| VmError::serialize_err(type_name::<T>(), e))
 }
 
+/// Serializes `data` into the given writer, producing the same output as [`to_vec`].
+pub fn to_writer<W, T>(writer: W, data: &T) -> VmResult<()>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    serde_json::to_writer(writer, data).map_err(|e| VmError::serialize_err(type_name::<T>(), e))
+}
+
+/// Returns the length of the output of [`to_vec`] without storing the serialized data.
+pub fn serialized_len<T>(data: &T) -> VmResult<usize>
+where
+    T: Serialize + ?Sized,
+{
+    let mut counter = LengthCounter(0);
+    to_writer(&mut counter, data)?;
+    Ok(counter.0)
+}
+
+/// A writer that discards all data and only counts the number of bytes written
+struct LengthCounter(usize);
+
+impl Write for LengthCounter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -53,6 +87,22 @@ mod tests {
         },
     }
 
+    #[test]
+    fn to_writer_and_serialized_len_match_to_vec() {
+        let msg = SomeMsg::ReleaseAll {
+            image: "foo\"bar".to_string(),
+            amount: 42,
+            time: 9007199254740999,
+            karma: -17,
+        };
+        let expected = to_vec(&msg).unwrap();
+
+        let mut written = Vec::new();
+        to_writer(&mut written, &msg).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!(serialized_len(&msg).unwrap(), expected.len());
+    }
+
     #[test]
     fn from_slice_works() {
         let deserialized: SomeMsg = from_slice(br#"{"refund":{}}"#, LIMIT).unwrap();
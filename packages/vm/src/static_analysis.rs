@@ -12,6 +12,11 @@ pub const REQUIRED_IBC_EXPORTS: &[&str] = &[
     "ibc_packet_timeout",
 ];
 
+/// Deserializes the Wasm bytecode for static analysis.
+///
+/// The DataCount section of the bulk memory proposal, which some toolchains emit even if
+/// no bulk memory operations are used, is accepted. Bulk memory operations themselves are
+/// not supported and fail with a deserialization error.
 pub fn deserialize_wasm(wasm_code: &[u8]) -> VmResult<Module> {
     deserialize_buffer(wasm_code).map_err(|err| {
         VmError::static_validation_err(format!(
@@ -82,6 +87,18 @@ pub fn has_ibc_entry_points(module: &impl ExportInfo) -> bool {
         .all(|required| available_exports.contains(*required))
 }
 
+/// Returns the payload of the first custom section with the given name, e.g. to read
+/// metadata embedded in the contract at upload time without instantiating it.
+///
+/// Returns `None` if no such section exists or the Wasm bytecode cannot be deserialized.
+pub fn read_custom_section(wasm_code: &[u8], name: &str) -> Option<Vec<u8>> {
+    let module = deserialize_wasm(wasm_code).ok()?;
+    let section = module
+        .custom_sections()
+        .find(|section| section.name() == name)?;
+    Some(section.payload().to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,6 +130,32 @@ mod tests {
         assert_eq!(exported_memories.count(), 1);
     }
 
+    #[test]
+    fn read_custom_section_works() {
+        let wasm = wat::parse_str(
+            r#"(module
+                (@custom "cw2" "crates.io:hackatom 1.2.3")
+                (@custom "build_info" "rustc 1.60.0")
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_custom_section(&wasm, "cw2").unwrap(),
+            b"crates.io:hackatom 1.2.3"
+        );
+        assert_eq!(
+            read_custom_section(&wasm, "build_info").unwrap(),
+            b"rustc 1.60.0"
+        );
+
+        // missing section
+        assert_eq!(read_custom_section(&wasm, "other"), None);
+        assert_eq!(read_custom_section(CONTRACT, "cw2"), None);
+
+        // corrupted Wasm
+        assert_eq!(read_custom_section(CORRUPTED, "cw2"), None);
+    }
+
     #[test]
     fn deserialize_wasm_corrupted_data() {
         match deserialize_wasm(CORRUPTED).unwrap_err() {
@@ -1,10 +1,15 @@
 //! This file has some helpers for integration tests.
 //! They should be imported via full path to ensure there is no confusion
 //! use cosmwasm_vm::testing::X
+use std::collections::HashMap;
+
 use schemars::JsonSchema;
 use serde::{de::DeserializeOwned, Serialize};
 
-use cosmwasm_std::{ContractResult, CustomMsg, Env, MessageInfo, QueryResponse, Reply, Response};
+use cosmwasm_std::{
+    ContractResult, CustomMsg, Env, MessageInfo, QueryResponse, Reply, ReplyOn, Response, SubMsg,
+    SubMsgResult,
+};
 #[cfg(feature = "stargate")]
 use cosmwasm_std::{
     Ibc3ChannelOpenResponse, IbcBasicResponse, IbcChannelCloseMsg, IbcChannelConnectMsg,
@@ -13,17 +18,22 @@ use cosmwasm_std::{
 };
 
 use crate::calls::{
-    call_execute, call_instantiate, call_migrate, call_query, call_reply, call_sudo,
+    call_execute, call_execute_raw, call_instantiate, call_instantiate_raw, call_migrate,
+    call_migrate_raw, call_migrate_with_info, call_query, call_query_raw, call_reply,
+    call_reply_raw, call_sudo, call_sudo_raw,
 };
 #[cfg(feature = "stargate")]
 use crate::calls::{
     call_ibc_channel_close, call_ibc_channel_connect, call_ibc_channel_open, call_ibc_packet_ack,
     call_ibc_packet_receive, call_ibc_packet_timeout,
 };
+use crate::errors::VmResult;
 use crate::instance::Instance;
 use crate::serde::to_vec;
 use crate::{BackendApi, Querier, Storage};
 
+use super::mock::{mock_env, mock_info};
+
 /// Mimicks the call signature of the smart contracts.
 /// Thus it moves env and msg rather than take them as reference.
 /// This is inefficient here, but only used in test code.
@@ -83,6 +93,27 @@ where
     call_migrate(instance, &env, &serialized_msg).expect("VM error")
 }
 
+// migrate_with_info mimicks the call signature of smart contracts whose migrate
+// takes the MessageInfo of the migration.
+// thus it moves env, info and msg rather than take them as reference.
+// this is inefficient here, but only used in test code
+pub fn migrate_with_info<A, S, Q, M, U>(
+    instance: &mut Instance<A, S, Q>,
+    env: Env,
+    info: MessageInfo,
+    msg: M,
+) -> ContractResult<Response<U>>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    M: Serialize + JsonSchema,
+    U: DeserializeOwned + CustomMsg,
+{
+    let serialized_msg = to_vec(&msg).expect("Testing error: Could not seralize request message");
+    call_migrate_with_info(instance, &env, &info, &serialized_msg).expect("VM error")
+}
+
 // sudo mimicks the call signature of the smart contracts.
 // thus it moves env and msg rather than take them as reference.
 // this is inefficient here, but only used in test code
@@ -243,3 +274,427 @@ where
 {
     call_ibc_packet_timeout(instance, &env, &msg).expect("VM error")
 }
+
+/// Calls `execute` and processes the returned submessages like the chain would.
+///
+/// `handle_submsg` simulates the execution of a submessage by the chain and returns its result.
+/// For every submessage that requests a reply for this result, a [`Reply`] is created and
+/// passed to the contract's `reply` entry point. Submessages returned by replies are
+/// processed the same way, depth-first. As on chain, an error in a submessage without
+/// a reply on error or an error in a reply aborts the whole execution, and all storage
+/// changes made by `execute` and the replies are rolled back.
+///
+/// Returns the responses of `execute` and of all replies in the order they were created.
+/// This is intended for use in test code only, e.g. to test multi-step submessage logic.
+pub fn execute_with_replies<A, S, Q, M, U, H>(
+    instance: &mut Instance<A, S, Q>,
+    env: Env,
+    info: MessageInfo,
+    msg: M,
+    mut handle_submsg: H,
+) -> ContractResult<Vec<Response<U>>>
+where
+    A: BackendApi + 'static,
+    S: Storage + Clone + 'static,
+    Q: Querier + 'static,
+    M: Serialize + JsonSchema,
+    U: DeserializeOwned + CustomMsg,
+    H: FnMut(&SubMsg<U>) -> SubMsgResult,
+{
+    let snapshot = instance
+        .with_storage(|storage| Ok(storage.clone()))
+        .expect("VM error");
+    let mut responses = vec![];
+    let result = execute(instance, env.clone(), info, msg)
+        .into_result()
+        .and_then(|response| {
+            process_submessages(instance, &env, response, &mut handle_submsg, &mut responses)
+        });
+    match result {
+        Ok(()) => ContractResult::Ok(responses),
+        Err(err) => {
+            instance
+                .with_storage(|storage| {
+                    *storage = snapshot;
+                    Ok(())
+                })
+                .expect("VM error");
+            ContractResult::Err(err)
+        }
+    }
+}
+
+fn process_submessages<A, S, Q, U, H>(
+    instance: &mut Instance<A, S, Q>,
+    env: &Env,
+    response: Response<U>,
+    handle_submsg: &mut H,
+    responses: &mut Vec<Response<U>>,
+) -> Result<(), String>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    U: DeserializeOwned + CustomMsg,
+    H: FnMut(&SubMsg<U>) -> SubMsgResult,
+{
+    let submessages = response.messages.clone();
+    responses.push(response);
+    for submsg in submessages {
+        let result = handle_submsg(&submsg);
+        let wants_reply = match (&submsg.reply_on, &result) {
+            (ReplyOn::Always, _) => true,
+            (ReplyOn::Success, SubMsgResult::Ok(_)) => true,
+            (ReplyOn::Error, SubMsgResult::Err(_)) => true,
+            (_, SubMsgResult::Err(err)) => return Err(err.clone()),
+            (_, SubMsgResult::Ok(_)) => false,
+        };
+        if wants_reply {
+            let msg = Reply {
+                id: submsg.id,
+                result,
+            };
+            let reply_response = reply(instance, env.clone(), msg).into_result()?;
+            process_submessages(instance, env, reply_response, handle_submsg, responses)?;
+        }
+    }
+    Ok(())
+}
+
+/// Calls the given entry point with `msg` and reports how much gas each import consumed
+/// during the call, keyed by import name. Imports that were not called have no entry.
+///
+/// `env` is [`mock_env`] and, for instantiate and execute, `info` is [`mock_info`] with
+/// sender "creator" and no funds. The result of the contract call is discarded.
+///
+/// This is intended for use in test code only, e.g. to find the most expensive
+/// host calls of a contract.
+pub fn run_and_report_imports<A, S, Q, M>(
+    instance: &mut Instance<A, S, Q>,
+    entry_point: &str,
+    msg: M,
+) -> HashMap<&'static str, u64>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    M: Serialize + JsonSchema,
+{
+    instance.start_import_gas_tracking();
+    let result = call_raw_with_mocks(instance, entry_point, msg);
+    let report = instance.stop_import_gas_tracking();
+    result.expect("VM error");
+    report
+}
+
+/// Calls the given entry point with `msg` and panics with the actual usage if the call
+/// consumed more than `max_gas`. Returns the gas used by the call otherwise.
+///
+/// `env` and `info` are set like in [`run_and_report_imports`]. The result of the contract
+/// call is discarded, i.e. a contract error does not cause a panic.
+///
+/// This is intended for use in test code only, e.g. for regression tests of gas budgets.
+pub fn assert_gas_under<A, S, Q, M>(
+    instance: &mut Instance<A, S, Q>,
+    entry_point: &str,
+    msg: M,
+    max_gas: u64,
+) -> u64
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    M: Serialize + JsonSchema,
+{
+    call_raw_with_mocks(instance, entry_point, msg).expect("VM error");
+    let report = instance.create_gas_report();
+    let used = report.used_internally + report.used_externally;
+    if used > max_gas {
+        panic!(
+            "Gas usage of {} is {}, which exceeds the limit of {}",
+            entry_point, used, max_gas
+        );
+    }
+    used
+}
+
+/// Calls the given entry point by name using [`mock_env`] and [`mock_info`] with
+/// sender "creator" and no funds
+fn call_raw_with_mocks<A, S, Q, M>(
+    instance: &mut Instance<A, S, Q>,
+    entry_point: &str,
+    msg: M,
+) -> VmResult<Vec<u8>>
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    M: Serialize + JsonSchema,
+{
+    let env = to_vec(&mock_env()).expect("Testing error: Could not serialize env");
+    let info = to_vec(&mock_info("creator", &[])).expect("Testing error: Could not serialize info");
+    let msg = to_vec(&msg).expect("Testing error: Could not seralize request message");
+
+    match entry_point {
+        "instantiate" => call_instantiate_raw(instance, &env, &info, &msg),
+        "execute" => call_execute_raw(instance, &env, &info, &msg),
+        "migrate" => call_migrate_raw(instance, &env, &msg),
+        "sudo" => call_sudo_raw(instance, &env, &msg),
+        "reply" => call_reply_raw(instance, &env, &msg),
+        "query" => call_query_raw(instance, &env, &msg),
+        _ => panic!("Testing error: Unsupported entry point {}", entry_point),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::GasConfig;
+    use crate::testing::{bump_allocator_contract, mock_instance};
+    use cosmwasm_std::Empty;
+
+    static CONTRACT: &[u8] = include_bytes!("../../testdata/hackatom.wasm");
+
+    /// Imports and body of a contract whose `execute` reads the storage, verifies
+    /// a secp256k1 signature using the message as all inputs and prints it twice
+    static IMPORTS_CONTRACT: (&str, &str) = (
+        r#"
+        (import "env" "db_read" (func $db_read (param i32) (result i32)))
+        (import "env" "secp256k1_verify" (func $secp256k1_verify (param i32 i32 i32) (result i32)))
+        (import "env" "debug" (func $debug (param i32)))
+        "#,
+        r#"
+        (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 256))
+        (func (export "execute") (param $env i32) (param $info i32) (param $msg i32) (result i32)
+            (drop (call $db_read (local.get $msg)))
+            (drop (call $secp256k1_verify (local.get $msg) (local.get $msg) (local.get $msg)))
+            (call $debug (local.get $msg))
+            (call $debug (local.get $msg))
+            (i32.const 256))
+        ;; Region { offset: 512, capacity: 0, length: 0 }
+        (data (i32.const 256) "\00\02\00\00\00\00\00\00\00\00\00\00")
+        "#,
+    );
+
+    #[test]
+    fn run_and_report_imports_works() {
+        let wasm = bump_allocator_contract(IMPORTS_CONTRACT.0, IMPORTS_CONTRACT.1);
+        let mut instance = mock_instance(&wasm, &[]);
+
+        let report = run_and_report_imports(&mut instance, "execute", Empty {});
+        assert_eq!(report.len(), 3);
+        let gas_config = GasConfig::default();
+        assert!(report["db_read"] > 0);
+        assert!(report["secp256k1_verify"] >= gas_config.secp256k1_verify_cost);
+        // debug has no cost of its own and is charged the minimum import cost once per call
+        assert_eq!(report["debug"], 2 * gas_config.min_import_cost);
+
+        // entry point without import calls
+        let report = run_and_report_imports(&mut instance, "instantiate", Empty {});
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn run_and_report_imports_works_for_hackatom() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let msg = br#"{"verifier": "verifies", "beneficiary": "benefits"}"#;
+        let env = to_vec(&mock_env()).unwrap();
+        let info = to_vec(&mock_info("creator", &[])).unwrap();
+        call_instantiate_raw(&mut instance, &env, &info, msg).unwrap();
+
+        let report =
+            run_and_report_imports(&mut instance, "query", serde_json::json!({"verifier": {}}));
+        assert!(report["db_read"] > 0);
+        assert!(!report.contains_key("secp256k1_verify"));
+
+        // tracking is stopped after the call
+        assert!(instance.stop_import_gas_tracking().is_empty());
+    }
+
+    #[test]
+    fn assert_gas_under_works() {
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let msg = serde_json::json!({"verifier": "creator", "beneficiary": "benefits"});
+        assert_gas_under(&mut instance, "instantiate", msg, 1_000_000_000_000);
+
+        // the sender "creator" is the verifier, so this takes the release path
+        let used = assert_gas_under(
+            &mut instance,
+            "execute",
+            serde_json::json!({"release": {}}),
+            1_000_000_000_000,
+        );
+        assert!(used > 0);
+    }
+
+    #[test]
+    fn assert_gas_under_panics_with_actual_usage() {
+        let msg = serde_json::json!({"verifier": "creator", "beneficiary": "benefits"});
+        let used = assert_gas_under(
+            &mut mock_instance(CONTRACT, &[]),
+            "instantiate",
+            msg.clone(),
+            u64::MAX,
+        );
+
+        // a fresh instance uses the same amount of gas
+        let mut instance = mock_instance(CONTRACT, &[]);
+        let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            assert_gas_under(&mut instance, "instantiate", msg, 1)
+        }))
+        .unwrap_err();
+        let message = err.downcast_ref::<String>().unwrap();
+        assert_eq!(
+            message,
+            &format!(
+                "Gas usage of instantiate is {}, which exceeds the limit of 1",
+                used
+            )
+        );
+    }
+
+    /// Builds a contract with a simple bump allocator whose `execute` returns
+    /// `execute_response` and whose `reply` writes "replied" -> "1" to storage
+    /// and returns `reply_response`. `instantiate` returns `reply_response` as well.
+    fn reply_contract(execute_response: &[u8], reply_response: &[u8]) -> Vec<u8> {
+        fn escape(data: &[u8]) -> String {
+            data.iter().map(|byte| format!("\\{:02x}", byte)).collect()
+        }
+        fn region(offset: u32, length: usize) -> String {
+            let length = length as u32;
+            escape(&[offset, length, length].map(u32::to_le_bytes).concat())
+        }
+
+        bump_allocator_contract(
+            r#"(import "env" "db_write" (func $db_write (param i32 i32)))"#,
+            &format!(
+                r#"
+                (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 272))
+                (func (export "execute") (param i32 i32 i32) (result i32) (i32.const 256))
+                (func (export "reply") (param i32 i32) (result i32)
+                    (call $db_write (i32.const 288) (i32.const 300))
+                    (i32.const 272))
+                (data (i32.const 256) "{}")
+                (data (i32.const 272) "{}")
+                (data (i32.const 288) "{}")
+                (data (i32.const 300) "{}")
+                (data (i32.const 4096) "{}")
+                (data (i32.const 12288) "{}")
+                (data (i32.const 16384) "replied")
+                (data (i32.const 16448) "1")
+            "#,
+                region(4096, execute_response.len()),
+                region(12288, reply_response.len()),
+                region(16384, 7),
+                region(16448, 1),
+                escape(execute_response),
+                escape(reply_response),
+            ),
+        )
+    }
+
+    #[test]
+    fn execute_with_replies_works() {
+        use cosmwasm_std::{SubMsgResponse, WasmMsg};
+
+        let instantiate_sub = WasmMsg::Instantiate {
+            admin: None,
+            code_id: 17,
+            msg: b"{}".into(),
+            funds: vec![],
+            label: "sub".to_string(),
+        };
+        let execute_response = ContractResult::Ok(
+            Response::<Empty>::new()
+                .add_submessage(SubMsg::reply_on_success(instantiate_sub.clone(), 1)),
+        );
+        let reply_response =
+            ContractResult::Ok(Response::<Empty>::new().add_attribute("action", "reply"));
+        let wasm = reply_contract(
+            &to_vec(&execute_response).unwrap(),
+            &to_vec(&reply_response).unwrap(),
+        );
+        let mut instance = mock_instance(&wasm, &[]);
+
+        let mut handled = vec![];
+        let responses: Vec<Response<Empty>> = execute_with_replies(
+            &mut instance,
+            mock_env(),
+            mock_info("creator", &[]),
+            Empty {},
+            |submsg| {
+                handled.push(submsg.id);
+                SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                })
+            },
+        )
+        .unwrap();
+        assert_eq!(handled, [1]);
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses[0].messages,
+            [SubMsg::reply_on_success(instantiate_sub, 1)]
+        );
+        assert_eq!(responses[1], reply_response.unwrap());
+
+        // the reply was executed against the contract state
+        let stored = instance
+            .with_storage(|storage| Ok(storage.get(b"replied").0.unwrap()))
+            .unwrap();
+        assert_eq!(stored, Some(b"1".to_vec()));
+
+        // failing submessages without reply on error abort the execution
+        let result: ContractResult<Vec<Response<Empty>>> = execute_with_replies(
+            &mut instance,
+            mock_env(),
+            mock_info("creator", &[]),
+            Empty {},
+            |_| SubMsgResult::Err("instantiation failed".to_string()),
+        );
+        assert_eq!(result.unwrap_err(), "instantiation failed");
+    }
+
+    #[test]
+    fn execute_with_replies_rolls_back_storage_on_error() {
+        use cosmwasm_std::{BankMsg, SubMsgResponse};
+
+        let send = BankMsg::Send {
+            to_address: "friend".to_string(),
+            amount: vec![],
+        };
+        // the reply to the first submessage writes to storage before the second one fails
+        let execute_response = ContractResult::Ok(
+            Response::<Empty>::new()
+                .add_submessage(SubMsg::reply_on_success(send.clone(), 1))
+                .add_message(send),
+        );
+        let reply_response = ContractResult::Ok(Response::<Empty>::new());
+        let wasm = reply_contract(
+            &to_vec(&execute_response).unwrap(),
+            &to_vec(&reply_response).unwrap(),
+        );
+        let mut instance = mock_instance(&wasm, &[]);
+
+        let result: ContractResult<Vec<Response<Empty>>> = execute_with_replies(
+            &mut instance,
+            mock_env(),
+            mock_info("creator", &[]),
+            Empty {},
+            |submsg| match submsg.id {
+                1 => SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+                _ => SubMsgResult::Err("insufficient funds".to_string()),
+            },
+        );
+        assert_eq!(result.unwrap_err(), "insufficient funds");
+
+        let stored = instance
+            .with_storage(|storage| Ok(storage.get(b"replied").0.unwrap()))
+            .unwrap();
+        assert_eq!(stored, None);
+    }
+}
@@ -2,7 +2,7 @@ use wasmer::Module;
 
 use crate::backend::{Backend, Storage};
 use crate::compatibility::check_wasm;
-use crate::instance::Instance;
+use crate::instance::{Instance, InstanceOptions};
 use crate::size::Size;
 use crate::wasm_backend::compile;
 
@@ -63,14 +63,12 @@ impl Contract {
             storage,
             querier,
         };
-        let instance = Instance::from_module(
-            &self.module,
-            backend,
-            options.gas_limit,
-            options.print_debug,
-            None,
-            None,
-        )?;
+        let instance_options = InstanceOptions {
+            gas_limit: options.gas_limit,
+            print_debug: options.print_debug,
+            ..options.instance_options.clone()
+        };
+        let instance = Instance::from_module(&self.module, backend, instance_options, None, None)?;
         Ok(instance)
     }
 
@@ -6,7 +6,7 @@ use std::collections::HashSet;
 
 use crate::capabilities::capabilities_from_csv;
 use crate::compatibility::check_wasm;
-use crate::instance::{Instance, InstanceOptions};
+use crate::instance::{Instance, InstanceOptions, DEFAULT_MAX_MESSAGES_PER_RESPONSE};
 use crate::size::Size;
 use crate::{Backend, BackendApi, Querier, Storage};
 
@@ -20,6 +20,8 @@ use super::storage::MockStorage;
 const DEFAULT_GAS_LIMIT: u64 = 500_000_000_000; // ~0.5ms
 const DEFAULT_MEMORY_LIMIT: Option<Size> = Some(Size::mebi(16));
 const DEFAULT_PRINT_DEBUG: bool = true;
+/// Gas limit used by [`MockInstanceOptions::high_gas`]. Allows many expensive calls on one instance.
+const HIGH_GAS_LIMIT: u64 = 20_000_000_000_000_000; // ~20s
 
 pub fn mock_instance(
     wasm: &[u8],
@@ -93,12 +95,35 @@ pub struct MockInstanceOptions<'a> {
     pub print_debug: bool,
     /// Memory limit in bytes. Use a value that is divisible by the Wasm page size 65536, e.g. full MiBs.
     pub memory_limit: Option<Size>,
+    /// All other options of the instance. Its `gas_limit` and `print_debug` are replaced by the
+    /// fields above.
+    pub instance_options: InstanceOptions,
 }
 
 impl MockInstanceOptions<'_> {
+    /// Default options with a gas limit high enough for many expensive calls on one instance
+    pub fn high_gas() -> Self {
+        Self {
+            gas_limit: HIGH_GAS_LIMIT,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the memory limit. Use a value that is divisible by the Wasm page size 65536, e.g. full MiBs.
+    pub fn with_memory_limit(mut self, memory_limit: Size) -> Self {
+        self.memory_limit = Some(memory_limit);
+        self
+    }
+
+    /// Enables printing of debug messages emitted by the contract
+    pub fn with_debug(mut self) -> Self {
+        self.print_debug = true;
+        self
+    }
+
     fn default_capabilities() -> HashSet<String> {
         #[allow(unused_mut)]
-        let mut out = capabilities_from_csv("iterator,staking,cosmwasm_1_1");
+        let mut out = capabilities_from_csv("iterator,staking,cosmwasm_1_1,cosmwasm_1_3");
         #[cfg(feature = "stargate")]
         out.insert("stargate".to_string());
         out
@@ -118,6 +143,7 @@ impl Default for MockInstanceOptions<'_> {
             gas_limit: DEFAULT_GAS_LIMIT,
             print_debug: DEFAULT_PRINT_DEBUG,
             memory_limit: DEFAULT_MEMORY_LIMIT,
+            instance_options: mock_instance_options().0,
         }
     }
 }
@@ -154,6 +180,7 @@ pub fn mock_instance_with_options(
     let options = InstanceOptions {
         gas_limit: options.gas_limit,
         print_debug: options.print_debug,
+        ..options.instance_options
     };
     Instance::from_code(wasm, backend, options, memory_limit).unwrap()
 }
@@ -164,11 +191,57 @@ pub fn mock_instance_options() -> (InstanceOptions, Option<Size>) {
         InstanceOptions {
             gas_limit: DEFAULT_GAS_LIMIT,
             print_debug: DEFAULT_PRINT_DEBUG,
+            max_messages_per_response: Some(DEFAULT_MAX_MESSAGES_PER_RESPONSE),
+            ..Default::default()
         },
         DEFAULT_MEMORY_LIMIT,
     )
 }
 
+/// Runs `func` and asserts that it leaves the storage of the instance in readonly mode.
+/// Use this to ensure an entry point like `query` cannot write to storage.
+pub fn assert_storage_readonly<A, S, Q, F, T>(instance: &mut Instance<A, S, Q>, func: F) -> T
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    F: FnOnce(&mut Instance<A, S, Q>) -> T,
+{
+    run_and_check_storage_readonly(instance, true, func)
+}
+
+/// Runs `func` and asserts that it leaves the storage of the instance writable.
+/// Use this to ensure an entry point like `execute` can write to storage.
+pub fn assert_storage_writable<A, S, Q, F, T>(instance: &mut Instance<A, S, Q>, func: F) -> T
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    F: FnOnce(&mut Instance<A, S, Q>) -> T,
+{
+    run_and_check_storage_readonly(instance, false, func)
+}
+
+fn run_and_check_storage_readonly<A, S, Q, F, T>(
+    instance: &mut Instance<A, S, Q>,
+    expected_readonly: bool,
+    func: F,
+) -> T
+where
+    A: BackendApi + 'static,
+    S: Storage + 'static,
+    Q: Querier + 'static,
+    F: FnOnce(&mut Instance<A, S, Q>) -> T,
+{
+    let result = func(instance);
+    assert_eq!(
+        instance.is_storage_readonly(),
+        expected_readonly,
+        "Unexpected storage readonly flag"
+    );
+    result
+}
+
 /// Runs a series of IO tests, hammering especially on allocate and deallocate.
 /// This could be especially useful when run with some kind of leak detector.
 pub fn test_io<A, S, Q>(instance: &mut Instance<A, S, Q>)
@@ -201,3 +274,55 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_instance_options_presets_work() {
+        let options = MockInstanceOptions::default();
+        assert_eq!(options.gas_limit, DEFAULT_GAS_LIMIT);
+        assert_eq!(
+            options.memory_limit.unwrap().0,
+            DEFAULT_MEMORY_LIMIT.unwrap().0
+        );
+
+        let options = MockInstanceOptions::high_gas();
+        assert_eq!(options.gas_limit, HIGH_GAS_LIMIT);
+        assert_eq!(
+            options.memory_limit.unwrap().0,
+            DEFAULT_MEMORY_LIMIT.unwrap().0
+        );
+
+        let options = MockInstanceOptions::high_gas().with_memory_limit(Size::mebi(500));
+        assert_eq!(options.gas_limit, HIGH_GAS_LIMIT);
+        assert_eq!(options.memory_limit.unwrap().0, Size::mebi(500).0);
+
+        let options = MockInstanceOptions {
+            print_debug: false,
+            ..Default::default()
+        }
+        .with_debug();
+        assert_eq!(options.gas_limit, DEFAULT_GAS_LIMIT);
+        assert!(options.print_debug);
+    }
+
+    #[test]
+    fn mock_instance_supports_cosmwasm_1_3() {
+        let wasm = wat::parse_str(
+            r#"(module
+            (memory 1)
+            (export "memory" (memory 0))
+            (func (export "interface_version_8"))
+            (func (export "allocate") (param i32) (result i32) (i32.const 0))
+            (func (export "deallocate") (param i32))
+            (func (export "instantiate") (param i32 i32 i32) (result i32) (i32.const 0))
+            (func (export "requires_cosmwasm_1_3"))
+        )"#,
+        )
+        .unwrap();
+        let instance = mock_instance(&wasm, &[]);
+        assert!(instance.required_capabilities().contains("cosmwasm_1_3"));
+    }
+}
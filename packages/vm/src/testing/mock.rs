@@ -1,7 +1,7 @@
-use cosmwasm_std::testing::{digit_sum, riffle_shuffle};
-use cosmwasm_std::{
-    Addr, BlockInfo, Coin, ContractInfo, Env, MessageInfo, Timestamp, TransactionInfo,
+use cosmwasm_std::testing::{
+    digit_sum, riffle_shuffle, FIXED_BLOCK_HEIGHT, FIXED_BLOCK_TIME, FIXED_CHAIN_ID,
 };
+use cosmwasm_std::{Addr, BlockInfo, Coin, ContractInfo, Env, MessageInfo, TransactionInfo};
 
 use super::querier::MockQuerier;
 use super::storage::MockStorage;
@@ -33,6 +33,19 @@ pub fn mock_backend_with_balances(
     }
 }
 
+/// Initializes a backend whose storage charges the given amounts of externally used gas
+/// per read and per write/removal
+pub fn mock_backend_with_gas_costs(
+    read_cost: u64,
+    write_cost: u64,
+) -> Backend<MockApi, MockStorage, MockQuerier> {
+    Backend {
+        api: MockApi::default(),
+        storage: MockStorage::new_with_gas_costs(read_cost, write_cost),
+        querier: MockQuerier::new(&[]),
+    }
+}
+
 /// Length of canonical addresses created with this API. Contracts should not make any assumtions
 /// what this value is.
 /// The value here must be restorable with `SHUFFLES_ENCODE` + `SHUFFLES_DECODE` in-shuffles.
@@ -41,14 +54,19 @@ const CANONICAL_LENGTH: usize = 54;
 const SHUFFLES_ENCODE: usize = 18;
 const SHUFFLES_DECODE: usize = 2;
 
-/// Zero-pads all human addresses to make them fit the canonical_length and
-/// trims off zeros for the reverse operation.
+/// Stores the length of the human address in the first byte, followed by the address itself,
+/// and zero-pads the result to make it fit the canonical_length. The length prefix makes the
+/// conversion lossless for all supported inputs, including ones containing NULL bytes.
+/// Human addresses can thus be at most `canonical_length - 1` bytes long.
 /// This is not really smart, but allows us to see a difference (and consistent length for canonical adddresses).
 #[derive(Copy, Clone)]
 pub struct MockApi {
     /// Length of canonical addresses created with this API. Contracts should not make any assumtions
     /// what this value is.
     canonical_length: usize,
+    /// Number of in-shuffles that restore a canonical address of `canonical_length`
+    /// after the `SHUFFLES_ENCODE` in-shuffles applied when encoding.
+    shuffles_decode: usize,
     /// `canonicalize_cost` is consumed gas value when the contract all api `canonical_address`
     canonicalize_cost: u64,
     /// `humanize_cost` is consumed gas value when the contract all api `human_address`
@@ -67,6 +85,28 @@ impl MockApi {
         }
     }
 
+    /// Returns a copy of this API creating canonical addresses of the given length.
+    ///
+    /// This allows testing contracts against chains with different address lengths,
+    /// e.g. 20 or 32 bytes. Human addresses can be at most `canonical_length - 1` bytes long.
+    ///
+    /// Panics if `canonical_length` is odd or not in the range 4..=256.
+    pub fn with_canonical_length(self, canonical_length: usize) -> Self {
+        assert!(
+            canonical_length % 2 == 0,
+            "Canonical length must be an even number"
+        );
+        assert!(
+            (4..=256).contains(&canonical_length),
+            "Canonical length must be in the range 4..=256"
+        );
+        MockApi {
+            canonical_length,
+            shuffles_decode: shuffles_decode(canonical_length),
+            ..self
+        }
+    }
+
     /// Read-only getter for `canonical_length`, which must not be changed by the caller.
     pub fn canonical_length(&self) -> usize {
         self.canonical_length
@@ -82,6 +122,12 @@ impl MockApi {
         self.humanize_cost
     }
 
+    /// The maximum length of human addresses supported by this API,
+    /// which is one byte less than `canonical_length` due to the length prefix.
+    fn max_human_length(&self) -> usize {
+        self.canonical_length - 1
+    }
+
     pub fn new_failing(backend_error: &'static str) -> Self {
         MockApi {
             backend_error: Some(backend_error),
@@ -94,6 +140,7 @@ impl Default for MockApi {
     fn default() -> Self {
         MockApi {
             canonical_length: CANONICAL_LENGTH,
+            shuffles_decode: SHUFFLES_DECODE,
             canonicalize_cost: DEFAULT_GAS_COST_CANONICALIZE,
             humanize_cost: DEFAULT_GAS_COST_HUMANIZE,
             backend_error: None,
@@ -101,6 +148,19 @@ impl Default for MockApi {
     }
 }
 
+/// Returns the number of in-shuffles needed after `SHUFFLES_ENCODE` in-shuffles
+/// to restore the original order of `length` elements.
+fn shuffles_decode(length: usize) -> usize {
+    let original: Vec<usize> = (0..length).collect();
+    let mut shuffled = riffle_shuffle(&original);
+    let mut period = 1;
+    while shuffled != original {
+        shuffled = riffle_shuffle(&shuffled);
+        period += 1;
+    }
+    (period - SHUFFLES_ENCODE % period) % period
+}
+
 impl BackendApi for MockApi {
     fn canonical_address(&self, input: &str) -> BackendResult<Vec<u8>> {
         // mimicks formats like hex or bech32 where different casings are valid for one address
@@ -121,7 +181,7 @@ impl BackendApi for MockApi {
                 gas_info,
             );
         }
-        if normalized.len() > self.canonical_length {
+        if normalized.len() > self.max_human_length() {
             return (
                 Err(BackendError::user_err(
                     "Invalid input: human address too long",
@@ -130,8 +190,10 @@ impl BackendApi for MockApi {
             );
         }
 
-        let mut out = Vec::from(normalized);
-        // pad to canonical length with NULL bytes
+        let mut out = Vec::with_capacity(self.canonical_length);
+        // length prefix, followed by the address and NULL bytes to pad to canonical length
+        out.push(normalized.len() as u8);
+        out.extend_from_slice(normalized.as_bytes());
         out.resize(self.canonical_length, 0x00);
         // content-dependent rotate followed by shuffle to destroy
         // the most obvious structure (https://github.com/CosmWasm/cosmwasm/issues/552)
@@ -160,15 +222,24 @@ impl BackendApi for MockApi {
         }
 
         let mut tmp: Vec<u8> = canonical.into();
-        // Shuffle two more times which restored the original value (24 elements are back to original after 20 rounds)
-        for _ in 0..SHUFFLES_DECODE {
+        // Shuffle until the original value is restored (54 elements are back to original after 20 rounds)
+        for _ in 0..self.shuffles_decode {
             tmp = riffle_shuffle(&tmp);
         }
         // Rotate back
         let rotate_by = digit_sum(&tmp) % self.canonical_length;
         tmp.rotate_right(rotate_by);
-        // Remove NULL bytes (i.e. the padding)
-        let trimmed = tmp.into_iter().filter(|&x| x != 0x00).collect();
+        // Remove length prefix and padding
+        let length = tmp[0] as usize;
+        if length > self.max_human_length() {
+            return (
+                Err(BackendError::user_err(
+                    "Invalid input: canonical address length prefix out of range",
+                )),
+                gas_info,
+            );
+        }
+        let trimmed = tmp[1..=length].to_vec();
 
         let result = match String::from_utf8(trimmed) {
             Ok(human) => Ok(human),
@@ -186,9 +257,9 @@ impl BackendApi for MockApi {
 pub fn mock_env() -> Env {
     Env {
         block: BlockInfo {
-            height: 12_345,
-            time: Timestamp::from_nanos(1_571_797_419_879_305_533),
-            chain_id: "cosmos-testnet-14002".to_string(),
+            height: FIXED_BLOCK_HEIGHT,
+            time: FIXED_BLOCK_TIME,
+            chain_id: FIXED_CHAIN_ID.to_string(),
         },
         transaction: Some(TransactionInfo { index: 3 }),
         contract: ContractInfo {
@@ -256,6 +327,107 @@ mod test {
         assert_eq!(recovered, "cosmwasmchef");
     }
 
+    #[test]
+    fn canonical_address_round_trip_is_lossless() {
+        use rand::Rng;
+
+        let api = MockApi::default();
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let length = rng.gen_range(3..=api.max_human_length());
+            // random ASCII, including NULL bytes, in its normalized (lower case) form
+            let human: String = (0..length)
+                .map(|_| rng.gen_range(0u8..128) as char)
+                .collect::<String>()
+                .to_lowercase();
+            let canonical = api.canonical_address(&human).0.unwrap();
+            assert_eq!(canonical.len(), api.canonical_length());
+            let recovered = api.human_address(&canonical).0.unwrap();
+            assert_eq!(recovered, human);
+        }
+
+        // trailing and embedded NULL bytes are preserved
+        for human in ["foo\0", "fo\0o", "\0\0\0", "foo\0\0\0\0\0\0\0\0\0\0\0\0\0"] {
+            let canonical = api.canonical_address(human).0.unwrap();
+            let recovered = api.human_address(&canonical).0.unwrap();
+            assert_eq!(recovered, human);
+        }
+    }
+
+    #[test]
+    fn canonical_address_max_human_length() {
+        let api = MockApi::default();
+        let human = "a".repeat(api.canonical_length() - 1);
+        let canonical = api.canonical_address(&human).0.unwrap();
+        assert_eq!(api.human_address(&canonical).0.unwrap(), human);
+
+        let human = "a".repeat(api.canonical_length());
+        match api.canonical_address(&human).0.unwrap_err() {
+            BackendError::UserErr { msg, .. } => {
+                assert_eq!(msg, "Invalid input: human address too long")
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn with_canonical_length_works() {
+        for canonical_length in [20, 32] {
+            let api = MockApi::default().with_canonical_length(canonical_length);
+            assert_eq!(api.canonical_length(), canonical_length);
+
+            for human in [
+                "foo",
+                "shorty",
+                "cosmwasmchef",
+                &"a".repeat(canonical_length - 1),
+            ] {
+                let canonical = api.canonical_address(human).0.unwrap();
+                assert_eq!(canonical.len(), canonical_length);
+                let recovered = api.human_address(&canonical).0.unwrap();
+                assert_eq!(recovered, human);
+            }
+
+            let human = "a".repeat(canonical_length);
+            match api.canonical_address(&human).0.unwrap_err() {
+                BackendError::UserErr { msg, .. } => {
+                    assert_eq!(msg, "Invalid input: human address too long")
+                }
+                err => panic!("Unexpected error: {:?}", err),
+            }
+
+            // canonical addresses of a different length are rejected
+            let other = MockApi::default().canonical_address("foobar").0.unwrap();
+            match api.human_address(&other).0.unwrap_err() {
+                BackendError::UserErr { msg, .. } => {
+                    assert_eq!(msg, "Invalid input: canonical address length not correct")
+                }
+                err => panic!("Unexpected error: {:?}", err),
+            }
+        }
+    }
+
+    #[test]
+    fn with_canonical_length_keeps_gas_costs() {
+        let api = MockApi::new_with_gas_cost(42, 101010).with_canonical_length(32);
+        assert_eq!(api.canonicalize_cost(), 42);
+        assert_eq!(api.humanize_cost(), 101010);
+    }
+
+    #[test]
+    #[should_panic(expected = "Canonical length must be an even number")]
+    fn with_canonical_length_panics_for_odd_length() {
+        let _ = MockApi::default().with_canonical_length(21);
+    }
+
+    #[test]
+    fn shuffles_decode_works() {
+        assert_eq!(shuffles_decode(CANONICAL_LENGTH), SHUFFLES_DECODE);
+        assert_eq!(shuffles_decode(20), 0); // period 6
+        assert_eq!(shuffles_decode(22), 4); // period 11
+        assert_eq!(shuffles_decode(32), 2); // period 10
+    }
+
     #[test]
     fn human_address_input_length() {
         let api = MockApi::default();
@@ -7,8 +7,13 @@ mod mock;
 mod querier;
 mod result;
 mod storage;
+#[cfg(test)]
+mod wat;
 
-pub use calls::{execute, instantiate, migrate, query, reply, sudo};
+pub use calls::{
+    assert_gas_under, execute, execute_with_replies, instantiate, migrate, migrate_with_info,
+    query, reply, run_and_report_imports, sudo,
+};
 #[cfg(feature = "stargate")]
 pub use calls::{
     ibc_channel_close, ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive,
@@ -16,13 +21,16 @@ pub use calls::{
 };
 pub use contract::Contract;
 pub use instance::{
-    mock_instance, mock_instance_options, mock_instance_with_balances,
-    mock_instance_with_failing_api, mock_instance_with_gas_limit, mock_instance_with_options,
-    test_io, MockInstanceOptions,
+    assert_storage_readonly, assert_storage_writable, mock_instance, mock_instance_options,
+    mock_instance_with_balances, mock_instance_with_failing_api, mock_instance_with_gas_limit,
+    mock_instance_with_options, test_io, MockInstanceOptions,
 };
 pub use mock::{
-    mock_backend, mock_backend_with_balances, mock_env, mock_info, MockApi, MOCK_CONTRACT_ADDR,
+    mock_backend, mock_backend_with_balances, mock_backend_with_gas_costs, mock_env, mock_info,
+    MockApi, MOCK_CONTRACT_ADDR,
 };
 pub use querier::MockQuerier;
 pub use result::{TestingError, TestingResult};
 pub use storage::MockStorage;
+#[cfg(test)]
+pub(crate) use wat::bump_allocator_contract;
@@ -35,6 +35,16 @@ impl<C: CustomQuery + DeserializeOwned> MockQuerier<C> {
         self.querier.update_balance(addr, balance)
     }
 
+    /// Sets new balances for all given addresses at once. Balances of other addresses are kept.
+    pub fn update_balances(&mut self, entries: &[(&str, Vec<Coin>)]) {
+        self.querier.update_balances(entries)
+    }
+
+    /// Removes the balances of all addresses
+    pub fn clear_balances(&mut self) {
+        self.querier.clear_balances()
+    }
+
     #[cfg(feature = "staking")]
     pub fn update_staking(
         &mut self,
@@ -52,6 +62,16 @@ impl<C: CustomQuery + DeserializeOwned> MockQuerier<C> {
         self.querier.update_wasm(handler)
     }
 
+    /// Sets the raw storage of the given contract, replacing any state set before.
+    /// `WasmQuery::Raw` requests for this contract return an empty value for missing keys.
+    pub fn update_contract_state(
+        &mut self,
+        contract_addr: impl Into<String>,
+        state: &[(&[u8], &[u8])],
+    ) {
+        self.querier.update_contract_state(contract_addr, state)
+    }
+
     #[must_use]
     pub fn with_custom_handler<CH: 'static>(mut self, handler: CH) -> Self
     where
@@ -114,7 +134,9 @@ impl MockQuerier {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cosmwasm_std::{coin, from_binary, AllBalanceResponse, BalanceResponse, BankQuery, Empty};
+    use cosmwasm_std::{
+        coin, from_binary, AllBalanceResponse, BalanceResponse, BankQuery, Empty, WasmQuery,
+    };
 
     const DEFAULT_QUERY_GAS_LIMIT: u64 = 300_000;
 
@@ -232,4 +254,40 @@ mod tests {
         let res: BalanceResponse = from_binary(&miss).unwrap();
         assert_eq!(res.amount, coin(0, "ELF"));
     }
+
+    #[test]
+    fn wasm_querier_contract_state_works() {
+        let mut querier = MockQuerier::new(&[]);
+        querier.update_contract_state("contract1", &[(b"count", b"42")]);
+
+        let value = querier
+            .query::<Empty>(
+                &WasmQuery::Raw {
+                    contract_addr: "contract1".to_string(),
+                    key: b"count".into(),
+                }
+                .into(),
+                DEFAULT_QUERY_GAS_LIMIT,
+            )
+            .0
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, Binary::from(b"42"));
+
+        let value = querier
+            .query::<Empty>(
+                &WasmQuery::Raw {
+                    contract_addr: "contract1".to_string(),
+                    key: b"missing".into(),
+                }
+                .into(),
+                DEFAULT_QUERY_GAS_LIMIT,
+            )
+            .0
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(value, Binary::default());
+    }
 }
@@ -29,6 +29,11 @@ pub struct MockStorage {
     data: BTreeMap<Vec<u8>, Vec<u8>>,
     #[cfg(feature = "iterator")]
     iterators: HashMap<u32, Iter>,
+    /// When set, every read is charged this amount of externally used gas instead of the key length
+    read_cost: Option<u64>,
+    /// When set, every write and removal is charged this amount of externally used gas
+    /// instead of the key (and value) length
+    write_cost: Option<u64>,
 }
 
 impl MockStorage {
@@ -36,6 +41,15 @@ impl MockStorage {
         MockStorage::default()
     }
 
+    /// create a `MockStorage` instance charging the given gas costs per operation
+    pub fn new_with_gas_costs(read_cost: u64, write_cost: u64) -> Self {
+        MockStorage {
+            read_cost: Some(read_cost),
+            write_cost: Some(write_cost),
+            ..MockStorage::default()
+        }
+    }
+
     #[cfg(feature = "iterator")]
     pub fn all(&mut self, iterator_id: u32) -> BackendResult<Vec<Record>> {
         let mut out: Vec<Record> = Vec::new();
@@ -60,10 +74,29 @@ impl MockStorage {
 
 impl Storage for MockStorage {
     fn get(&self, key: &[u8]) -> BackendResult<Option<Vec<u8>>> {
-        let gas_info = GasInfo::with_externally_used(key.len() as u64);
+        let gas_info = GasInfo::with_externally_used(self.read_cost.unwrap_or(key.len() as u64));
         (Ok(self.data.get(key).cloned()), gas_info)
     }
 
+    /// Charges the key length plus the length of the returned slice, such that the cost
+    /// does not depend on the size of the full value
+    fn get_range(
+        &self,
+        key: &[u8],
+        offset: usize,
+        length: usize,
+    ) -> BackendResult<Option<Vec<u8>>> {
+        let slice = self.data.get(key).map(|data| {
+            let start = offset.min(data.len());
+            let end = start.saturating_add(length).min(data.len());
+            data[start..end].to_vec()
+        });
+        let slice_len = slice.as_ref().map(|slice| slice.len()).unwrap_or_default();
+        let gas_info =
+            GasInfo::with_externally_used(self.read_cost.unwrap_or((key.len() + slice_len) as u64));
+        (Ok(slice), gas_info)
+    }
+
     #[cfg(feature = "iterator")]
     fn scan(
         &mut self,
@@ -126,13 +159,15 @@ impl Storage for MockStorage {
 
     fn set(&mut self, key: &[u8], value: &[u8]) -> BackendResult<()> {
         self.data.insert(key.to_vec(), value.to_vec());
-        let gas_info = GasInfo::with_externally_used((key.len() + value.len()) as u64);
+        let gas_info = GasInfo::with_externally_used(
+            self.write_cost.unwrap_or((key.len() + value.len()) as u64),
+        );
         (Ok(()), gas_info)
     }
 
     fn remove(&mut self, key: &[u8]) -> BackendResult<()> {
         self.data.remove(key);
-        let gas_info = GasInfo::with_externally_used(key.len() as u64);
+        let gas_info = GasInfo::with_externally_used(self.write_cost.unwrap_or(key.len() as u64));
         (Ok(()), gas_info)
     }
 }
@@ -0,0 +1,32 @@
+/// Builds a test contract with a simple bump allocator and the `interface_version_8`,
+/// `allocate` and `deallocate` exports.
+///
+/// `imports` are placed before and `body` after those definitions, since Wasm text format
+/// requires imports to come first. The memory has two pages. Allocations start at 32 KiB,
+/// so the body can place data segments below that. Allocated Regions are never freed.
+pub(crate) fn bump_allocator_contract(imports: &str, body: &str) -> Vec<u8> {
+    wat::parse_str(format!(
+        r#"(module
+            {}
+            (memory 2)
+            (export "memory" (memory 0))
+            (global $next (mut i32) (i32.const 32768))
+            (func (export "interface_version_8"))
+            (func (export "allocate") (param $size i32) (result i32)
+                (local $region i32)
+                (local.set $region (global.get $next))
+                (i32.store (local.get $region) (i32.add (local.get $region) (i32.const 12)))
+                (i32.store offset=4 (local.get $region) (local.get $size))
+                (i32.store offset=8 (local.get $region) (i32.const 0))
+                ;; keep Regions 4-byte aligned
+                (global.set $next (i32.and
+                    (i32.add (global.get $next) (i32.add (local.get $size) (i32.const 15)))
+                    (i32.const -4)))
+                (local.get $region))
+            (func (export "deallocate") (param i32))
+            {}
+        )"#,
+        imports, body
+    ))
+    .unwrap()
+}
@@ -1,8 +1,11 @@
-use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
 
 use wasmer::{Module, ModuleMiddleware};
 
-use crate::errors::VmResult;
+use crate::errors::{VmError, VmResult};
 use crate::size::Size;
 
 use super::store::make_compile_time_store;
@@ -21,6 +24,79 @@ pub fn compile(
     Ok(module)
 }
 
+/// Upper bound for the number of worker threads of [`compile_with_timeout`] at the same time.
+/// This includes compilations that exceeded their timeout but did not finish yet.
+const MAX_CONCURRENT_COMPILATIONS: usize = 16;
+
+/// The number of running worker threads of [`compile_with_timeout`]
+static RUNNING_COMPILATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// One of a limited number of slots for running a compilation. The slot is released on drop.
+struct CompilationSlot {
+    counter: &'static AtomicUsize,
+}
+
+impl CompilationSlot {
+    /// Takes a slot if less than `max` are taken
+    fn acquire(counter: &'static AtomicUsize, max: usize) -> Option<Self> {
+        counter
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |running| {
+                (running < max).then(|| running + 1)
+            })
+            .ok()
+            .map(|_| CompilationSlot { counter })
+    }
+}
+
+impl Drop for CompilationSlot {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Like [`compile`] but gives up after `timeout` if set.
+///
+/// The compilation runs on a worker thread. Compilation cannot be interrupted, so after a
+/// timeout the thread keeps running in the background until the compiler finishes and its
+/// result is dropped. To bound the resources used by such threads, at most
+/// `MAX_CONCURRENT_COMPILATIONS` worker threads run at the same time. Calls beyond that
+/// fail with a compile error.
+pub fn compile_with_timeout(
+    code: &[u8],
+    memory_limit: Option<Size>,
+    middlewares: &[Arc<dyn ModuleMiddleware>],
+    timeout: Option<Duration>,
+) -> VmResult<Module> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return compile(code, memory_limit, middlewares),
+    };
+
+    let slot = CompilationSlot::acquire(&RUNNING_COMPILATIONS, MAX_CONCURRENT_COMPILATIONS)
+        .ok_or_else(|| {
+            VmError::compile_err(format!(
+                "Too many compilations running, the limit is {}",
+                MAX_CONCURRENT_COMPILATIONS
+            ))
+        })?;
+    let code = code.to_vec();
+    let middlewares = middlewares.to_vec();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        // The slot is released when the compilation finished, independent of the timeout
+        let _slot = slot;
+        // The receiver is gone if the timeout was exceeded
+        let _ = sender.send(compile(&code, memory_limit, &middlewares));
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(VmError::compile_timeout(timeout)),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(VmError::compile_err(
+            "Compilation thread stopped without a result",
+        )),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -32,4 +108,48 @@ mod tests {
         let err = compile(CONTRACT, None, &[]).unwrap_err();
         assert!(err.to_string().contains("Float operator detected:"));
     }
+
+    #[test]
+    fn compile_with_timeout_works() {
+        static HACKATOM: &[u8] = include_bytes!("../../testdata/hackatom.wasm");
+
+        // no timeout
+        compile_with_timeout(HACKATOM, None, &[], None).unwrap();
+
+        // generous timeout
+        compile_with_timeout(HACKATOM, None, &[], Some(Duration::from_secs(600))).unwrap();
+
+        // tiny timeout
+        match compile_with_timeout(HACKATOM, None, &[], Some(Duration::from_nanos(1))).unwrap_err()
+        {
+            VmError::CompileTimeout { timeout, .. } => {
+                assert_eq!(timeout, Duration::from_nanos(1))
+            }
+            e => panic!("Unexpected error: {:?}", e),
+        }
+
+        // compile errors are passed through
+        let err =
+            compile_with_timeout(CONTRACT, None, &[], Some(Duration::from_secs(600))).unwrap_err();
+        assert!(err.to_string().contains("Float operator detected:"));
+    }
+
+    #[test]
+    fn compilation_slot_works() {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        let slot1 = CompilationSlot::acquire(&COUNTER, 2).unwrap();
+        let slot2 = CompilationSlot::acquire(&COUNTER, 2).unwrap();
+        assert!(CompilationSlot::acquire(&COUNTER, 2).is_none());
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 2);
+
+        // dropping a slot makes room for a new one
+        drop(slot1);
+        let slot3 = CompilationSlot::acquire(&COUNTER, 2).unwrap();
+        assert!(CompilationSlot::acquire(&COUNTER, 2).is_none());
+
+        drop(slot2);
+        drop(slot3);
+        assert_eq!(COUNTER.load(Ordering::SeqCst), 0);
+    }
 }